@@ -0,0 +1,54 @@
+//! C ABI for the API. Results are JSON strings owned by Rust and must be
+//! handed back to [`artifact_naming_free_string`].
+
+use std::ffi::{c_char, CStr};
+
+use ffi_support::{catch_panic, error_json, into_c_string};
+use serde_json::json;
+
+use crate::{slugify_url, Options};
+
+/// Returns `{"slug"}` for the NUL-terminated `url`, or `{"error"}`.
+/// `options` is an optional JSON object overriding [`Options`]
+/// (`{"extension": "png", "maxLength": 80}`).
+///
+/// # Safety
+/// `url` must be a valid NUL-terminated string and `options` null or one.
+/// The returned pointer must be released with [`artifact_naming_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn url_to_slug(url: *const c_char, options: *const c_char) -> *mut c_char {
+    catch_panic(|| {
+        if url.is_null() {
+            return error_json("url is null".to_string());
+        }
+        let Ok(url) = CStr::from_ptr(url).to_str() else {
+            return error_json("url is not valid UTF-8".to_string());
+        };
+        let options = if options.is_null() {
+            Options::default()
+        } else {
+            match CStr::from_ptr(options)
+                .to_str()
+                .map_err(|err| err.to_string())
+                .and_then(|options| serde_json::from_str(options).map_err(|err| err.to_string()))
+            {
+                Ok(options) => options,
+                Err(err) => return error_json(format!("invalid options: {err}")),
+            }
+        };
+        match slugify_url(url, &options) {
+            Ok(slug) => into_c_string(json!({ "slug": slug }).to_string()),
+            Err(err) => error_json(err.to_string()),
+        }
+    })
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+/// `ptr` must come from a function of this library and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn artifact_naming_free_string(ptr: *mut c_char) {
+    ffi_support::free_string(ptr);
+}
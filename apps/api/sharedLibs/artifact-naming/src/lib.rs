@@ -0,0 +1,203 @@
+//! Turns URLs into file names for stored artifacts (screenshots, raw HTML,
+//! PDFs). Names are lowercase ASCII, safe on every filesystem we write to,
+//! capped in length, and end in a hash of the full URL so two URLs that
+//! slugify the same way still get different names.
+
+use serde::Deserialize;
+use xxhash_rust::xxh3::xxh3_64;
+
+mod ffi;
+#[cfg(feature = "napi")]
+pub mod node;
+
+pub use ffi::{artifact_naming_free_string, url_to_slug};
+
+/// Device names Windows refuses as file stems, whatever the extension.
+const RESERVED_NAMES: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum NamingError {
+    #[error(
+        "maxLength {max_length} leaves no room for a name; the hash and extension need {required}"
+    )]
+    MaxLengthTooSmall { max_length: usize, required: usize },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Options {
+    /// Maximum length of the whole name, extension included.
+    pub max_length: usize,
+    /// Hex digits of the URL hash; 0 disables the suffix (and the collision
+    /// guarantee).
+    pub hash_length: usize,
+    /// Appended as `.{extension}` after sanitizing.
+    pub extension: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            max_length: 120,
+            hash_length: 12,
+            extension: None,
+        }
+    }
+}
+
+/// Fails when `max_length` can't fit at least one character besides the hash
+/// suffix and the extension.
+pub fn slugify_url(url: &str, options: &Options) -> Result<String, NamingError> {
+    let without_fragment = url.split('#').next().unwrap_or(url);
+    let without_scheme = without_fragment
+        .split_once("://")
+        .map_or(without_fragment, |(_, rest)| rest);
+    let without_www = without_scheme
+        .strip_prefix("www.")
+        .unwrap_or(without_scheme);
+
+    let extension = options
+        .extension
+        .as_deref()
+        .map(|extension| sanitize(extension.trim_start_matches('.')))
+        .filter(|extension| !extension.is_empty())
+        .map(|extension| format!(".{extension}"))
+        .unwrap_or_default();
+    let hash_length = options.hash_length.min(16);
+    let suffix = if hash_length == 0 {
+        String::new()
+    } else {
+        format!(
+            "-{}",
+            &format!("{:016x}", xxh3_64(url.as_bytes()))[..hash_length]
+        )
+    };
+
+    let required = extension.len() + suffix.len() + 1;
+    if options.max_length < required {
+        return Err(NamingError::MaxLengthTooSmall {
+            max_length: options.max_length,
+            required,
+        });
+    }
+    let budget = options.max_length - extension.len() - suffix.len();
+    let mut base = sanitize(without_www);
+    base.truncate(budget);
+    let mut base = base.trim_end_matches(['-', '.']).to_string();
+    if base.is_empty() {
+        base = "index".to_string();
+    }
+    let stem = base.split('.').next().unwrap_or(&base);
+    if suffix.is_empty() && RESERVED_NAMES.contains(&stem) {
+        base.insert(0, '_');
+        base.truncate(budget);
+    }
+    Ok(format!("{base}{suffix}{extension}"))
+}
+
+/// Lowercase `[a-z0-9._-]`, with every other run of characters collapsed to
+/// a single `-` and no leading dots or dashes (no hidden files, no options).
+fn sanitize(value: &str) -> String {
+    let mut slug = String::with_capacity(value.len());
+    for ch in value.chars().flat_map(char::to_lowercase) {
+        if ch.is_ascii_alphanumeric() || ch == '.' || ch == '_' {
+            slug.push(ch);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_start_matches(['-', '.'])
+        .trim_end_matches('-')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slug(url: &str) -> String {
+        slugify_url(url, &Options::default()).unwrap()
+    }
+
+    #[test]
+    fn builds_readable_hashed_names() {
+        let name = slug("https://www.Example.com/Blog/Hello World?page=2#top");
+        let (base, hash) = name.rsplit_once('-').unwrap();
+        assert_eq!(base, "example.com-blog-hello-world-page-2");
+        assert_eq!(hash.len(), 12);
+        assert!(hash.bytes().all(|b| b.is_ascii_hexdigit()));
+        assert_eq!(
+            name,
+            slug("https://www.Example.com/Blog/Hello World?page=2#top")
+        );
+    }
+
+    #[test]
+    fn distinguishes_urls_that_sanitize_alike() {
+        assert_ne!(
+            slug("https://example.com/a?b"),
+            slug("https://example.com/a/b")
+        );
+        assert_ne!(slug("https://example.com/A"), slug("https://example.com/a"));
+    }
+
+    #[test]
+    fn caps_length_including_extension() {
+        let options = Options {
+            max_length: 40,
+            extension: Some(".PNG".to_string()),
+            ..Options::default()
+        };
+        let name = slugify_url(
+            &format!("https://example.com/{}", "segment/".repeat(50)),
+            &options,
+        )
+        .unwrap();
+        assert!(name.len() <= 40, "{name}");
+        assert!(name.ends_with(".png"));
+        assert!(!name.contains("-.") && !name.contains("--"));
+    }
+
+    #[test]
+    fn rejects_lengths_that_cannot_fit_the_suffix() {
+        let options = |max_length| Options {
+            max_length,
+            extension: Some("png".to_string()),
+            ..Options::default()
+        };
+        // 13 for `-` and the hash, 4 for `.png`, 1 for the name itself.
+        assert!(matches!(
+            slugify_url("https://example.com/", &options(17)),
+            Err(NamingError::MaxLengthTooSmall { required: 18, .. })
+        ));
+        let name = slugify_url("https://example.com/", &options(18)).unwrap();
+        assert_eq!(name.len(), 18);
+        assert!(name.starts_with("e-") && name.ends_with(".png"));
+
+        let no_hash = Options {
+            max_length: 3,
+            hash_length: 0,
+            ..Options::default()
+        };
+        assert_eq!(slugify_url("con", &no_hash).unwrap(), "_co");
+    }
+
+    #[test]
+    fn avoids_hidden_traversal_and_reserved_names() {
+        let options = Options {
+            hash_length: 0,
+            ..Options::default()
+        };
+        assert_eq!(slugify_url("con", &options).unwrap(), "_con");
+        assert_eq!(
+            slugify_url("https://../../etc/passwd", &options).unwrap(),
+            "etc-passwd"
+        );
+        assert_eq!(slugify_url("https://", &options).unwrap(), "index");
+        assert_eq!(sanitize(".hidden"), "hidden");
+        assert_eq!(sanitize("Hello/Wörld"), "hello-w-rld");
+    }
+}
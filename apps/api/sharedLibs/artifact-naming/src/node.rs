@@ -0,0 +1,17 @@
+//! Node-API bindings, built with `--features napi`.
+
+use napi_derive::napi;
+
+use crate::{slugify_url, Options};
+
+/// Returns the slug itself; `options` is the same optional JSON object the C
+/// ABI takes. Throws where the C ABI returns `{"error"}`.
+#[napi(js_name = "urlToSlug")]
+pub fn url_to_slug(url: String, options: Option<String>) -> napi::Result<String> {
+    let options = match options {
+        Some(options) => serde_json::from_str::<Options>(&options)
+            .map_err(|err| napi::Error::from_reason(format!("invalid options: {err}")))?,
+        None => Options::default(),
+    };
+    slugify_url(&url, &options).map_err(|err| napi::Error::from_reason(err.to_string()))
+}
@@ -0,0 +1,98 @@
+//! C ABI for the API. Results are JSON strings owned by Rust and must be
+//! handed back to [`charset_free_string`].
+
+use std::ffi::{c_char, CStr};
+
+use ffi_support::{catch_panic, into_c_string};
+use serde_json::json;
+
+use crate::{decode, detect, Hints};
+
+unsafe fn hints(options: *const c_char) -> Result<Hints, String> {
+    if options.is_null() {
+        return Ok(Hints::default());
+    }
+    let options = CStr::from_ptr(options)
+        .to_str()
+        .map_err(|err| err.to_string())?;
+    serde_json::from_str(options).map_err(|err| format!("invalid options: {err}"))
+}
+
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if data.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, len)
+    }
+}
+
+/// Detects the encoding of `data[..len]` and returns the serialized
+/// [`crate::Detection`], or `{"error"}`. `options` is an optional JSON
+/// object with `contentType` and `url` hints.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes and `options` must be null or a
+/// valid NUL-terminated string. The returned pointer must be released with
+/// [`charset_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn charset_detect(
+    data: *const u8,
+    len: usize,
+    options: *const c_char,
+) -> *mut c_char {
+    catch_panic(|| match hints(options) {
+        Ok(hints) => into_c_string(json!(detect(bytes(data, len), &hints)).to_string()),
+        Err(err) => into_c_string(json!({ "error": err }).to_string()),
+    })
+}
+
+/// Like [`charset_detect`], but also returns the document decoded to UTF-8
+/// in `text` (serialized [`crate::Decoded`]).
+///
+/// # Safety
+/// Same as [`charset_detect`].
+#[no_mangle]
+pub unsafe extern "C" fn charset_decode(
+    data: *const u8,
+    len: usize,
+    options: *const c_char,
+) -> *mut c_char {
+    catch_panic(|| match hints(options) {
+        Ok(hints) => into_c_string(json!(decode(bytes(data, len), &hints)).to_string()),
+        Err(err) => into_c_string(json!({ "error": err }).to_string()),
+    })
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+/// `ptr` must come from a function of this library and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn charset_free_string(ptr: *mut c_char) {
+    ffi_support::free_string(ptr);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_nul_bytes_safely() {
+        let data = b"a\0b\xe9";
+        unsafe {
+            let ptr = charset_decode(
+                data.as_ptr(),
+                data.len(),
+                c"{\"contentType\":\"text/plain; charset=latin1\"}".as_ptr(),
+            );
+            let value: serde_json::Value =
+                serde_json::from_str(CStr::from_ptr(ptr).to_str().unwrap()).unwrap();
+            charset_free_string(ptr);
+            assert_eq!(value["text"], "a\u{0}bé");
+            assert_eq!(value["source"], "header");
+            assert_eq!(value["hadErrors"], false);
+            assert!(value.get("had_errors").is_none());
+        }
+    }
+}
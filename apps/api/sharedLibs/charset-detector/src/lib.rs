@@ -0,0 +1,222 @@
+//! Works out the character encoding of fetched bytes and decodes them to
+//! UTF-8 before they reach the HTML transformer or any other parser that
+//! crosses the C-string boundary. The order follows the HTML spec's encoding
+//! sniffing: byte order mark, transport `Content-Type`, `<meta>` prescan,
+//! then a statistical guess (chardetng) when nothing was declared.
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use serde::{Deserialize, Serialize};
+
+mod ffi;
+
+pub use ffi::{charset_decode, charset_detect, charset_free_string};
+
+/// How far into the document `<meta>` declarations are honoured, as in the
+/// HTML spec's prescan.
+const META_PRESCAN_BYTES: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Bom,
+    Header,
+    Meta,
+    /// No declaration, but the bytes are valid UTF-8.
+    Utf8,
+    Detected,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Hints {
+    /// The response `Content-Type` header.
+    pub content_type: Option<String>,
+    /// The page URL; its top-level domain steers the statistical guess.
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Detection {
+    /// WHATWG encoding name, e.g. `windows-1252` or `Shift_JIS`.
+    pub encoding: &'static str,
+    pub source: Source,
+    /// False when the statistical guess had little to go on.
+    pub confident: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Decoded {
+    #[serde(flatten)]
+    pub detection: Detection,
+    pub text: String,
+    /// Whether malformed sequences were replaced with U+FFFD.
+    pub had_errors: bool,
+}
+
+pub fn detect(data: &[u8], hints: &Hints) -> Detection {
+    let (encoding, source, confident) = sniff(data, hints);
+    Detection {
+        encoding: encoding.name(),
+        source,
+        confident,
+    }
+}
+
+pub fn decode(data: &[u8], hints: &Hints) -> Decoded {
+    let (encoding, source, confident) = sniff(data, hints);
+    // `decode` strips a BOM and lets it override the sniffed encoding.
+    let (text, actual, had_errors) = encoding.decode(data);
+    Decoded {
+        detection: Detection {
+            encoding: actual.name(),
+            source,
+            confident,
+        },
+        text: text.into_owned(),
+        had_errors,
+    }
+}
+
+fn sniff(data: &[u8], hints: &Hints) -> (&'static Encoding, Source, bool) {
+    if let Some((encoding, _)) = Encoding::for_bom(data) {
+        return (encoding, Source::Bom, true);
+    }
+    if let Some(encoding) = hints
+        .content_type
+        .as_deref()
+        .and_then(header_charset)
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+    {
+        return (encoding, Source::Header, true);
+    }
+    if let Some(encoding) = meta_charset(data) {
+        return (encoding, Source::Meta, true);
+    }
+    if std::str::from_utf8(data).is_ok() {
+        return (UTF_8, Source::Utf8, true);
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(data, true);
+    let tld = hints.url.as_deref().and_then(top_level_domain);
+    let (encoding, confident) = detector.guess_assess(tld.as_deref().map(str::as_bytes), true);
+    (encoding, Source::Detected, confident)
+}
+
+fn header_charset(content_type: &str) -> Option<&str> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches(['"', '\'']))
+    })
+}
+
+/// `<meta charset>` or `<meta http-equiv content="...; charset=...">` in the
+/// prescan window.
+fn meta_charset(data: &[u8]) -> Option<&'static Encoding> {
+    let head = &data[..data.len().min(META_PRESCAN_BYTES)];
+    let head: String = head
+        .iter()
+        .map(|byte| byte.to_ascii_lowercase() as char)
+        .collect();
+    let mut rest = head.as_str();
+    while let Some(start) = rest.find("<meta") {
+        let tag = &rest[start..];
+        let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+        if let Some(index) = tag.find("charset") {
+            let value = tag[index + "charset".len()..].trim_start();
+            if let Some(value) = value.strip_prefix('=') {
+                let value = value.trim_start().trim_start_matches(['"', '\'']);
+                let end = value
+                    .find(|ch: char| {
+                        ch == '"' || ch == '\'' || ch == ';' || ch.is_whitespace() || ch == '/'
+                    })
+                    .unwrap_or(value.len());
+                if let Some(encoding) = Encoding::for_label(&value.as_bytes()[..end]) {
+                    // A page can't really be UTF-16 if the prescan read it as
+                    // ASCII; the spec maps these declarations to UTF-8.
+                    return Some(match encoding {
+                        encoding if encoding == UTF_16LE || encoding == UTF_16BE => UTF_8,
+                        encoding if encoding.name() == "x-user-defined" => WINDOWS_1252,
+                        encoding => encoding,
+                    });
+                }
+            }
+        }
+        rest = &rest[start + "<meta".len()..];
+    }
+    None
+}
+
+fn top_level_domain(url: &str) -> Option<String> {
+    let host = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = host.split(['/', '?', '#', ':']).next()?;
+    let tld = host.rsplit('.').next()?;
+    (!tld.is_empty() && tld.chars().all(|ch| ch.is_ascii_alphabetic()))
+        .then(|| tld.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_bom_then_header_then_meta() {
+        let html = b"\xef\xbb\xbf<meta charset=\"iso-8859-2\">caf\xc3\xa9";
+        let decoded = decode(html, &Hints::default());
+        assert_eq!(decoded.detection.source, Source::Bom);
+        assert_eq!(decoded.text, "<meta charset=\"iso-8859-2\">café");
+
+        let html = b"<meta charset=\"windows-1252\">caf\xe9";
+        let hints = Hints {
+            content_type: Some("text/html; charset=\"ISO-8859-15\"".to_string()),
+            url: None,
+        };
+        let detection = detect(html, &hints);
+        assert_eq!(
+            (detection.encoding, detection.source),
+            ("ISO-8859-15", Source::Header)
+        );
+
+        let decoded = decode(html, &Hints::default());
+        assert_eq!(decoded.detection.source, Source::Meta);
+        assert_eq!(decoded.detection.encoding, "windows-1252");
+        assert!(decoded.text.ends_with("café"));
+    }
+
+    #[test]
+    fn reads_http_equiv_meta() {
+        let html = b"<html><head><META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=Shift_JIS\">";
+        assert_eq!(detect(html, &Hints::default()).encoding, "Shift_JIS");
+        let utf16 = b"<meta charset=utf-16le>";
+        assert_eq!(detect(utf16, &Hints::default()).encoding, "UTF-8");
+    }
+
+    #[test]
+    fn falls_back_to_utf8_and_detection() {
+        let utf8 = "Grüße".as_bytes();
+        assert_eq!(detect(utf8, &Hints::default()).source, Source::Utf8);
+
+        let (cyrillic, _, _) = encoding_rs::WINDOWS_1251
+            .encode("Привет, мир! Это страница на русском языке, и она довольно длинная.");
+        let hints = Hints {
+            content_type: None,
+            url: Some("https://example.ru/page".to_string()),
+        };
+        let decoded = decode(&cyrillic, &hints);
+        assert_eq!(decoded.detection.source, Source::Detected);
+        assert_eq!(decoded.detection.encoding, "windows-1251");
+        assert!(decoded.text.starts_with("Привет"));
+        assert!(!decoded.had_errors);
+    }
+
+    #[test]
+    fn extracts_top_level_domain() {
+        assert_eq!(
+            top_level_domain("https://www.example.co.jp:8080/a").as_deref(),
+            Some("jp")
+        );
+        assert_eq!(top_level_domain("http://127.0.0.1/"), None);
+    }
+}
@@ -0,0 +1,180 @@
+//! C ABI for the API. The index lives behind an opaque handle created with
+//! [`dedup_index_new`] or [`dedup_index_deserialize`] and released with
+//! [`dedup_index_free`]; a handle must not be used from two threads at once.
+//! Results are JSON strings owned by Rust and must be handed back to
+//! [`dedup_index_free_string`].
+
+use std::ffi::{c_char, CStr};
+use std::panic;
+use std::ptr;
+
+use ffi_support::{catch_panic, into_c_string};
+use serde_json::json;
+
+use crate::{DedupIndex, Options};
+
+unsafe fn optional_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        None
+    } else {
+        CStr::from_ptr(ptr).to_str().ok()
+    }
+}
+
+/// Creates an empty index. `options` is an optional JSON object overriding
+/// [`Options`]. Returns null when the options are invalid.
+///
+/// # Safety
+/// `options` must be null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dedup_index_new(options: *const c_char) -> *mut DedupIndex {
+    let options = match optional_str(options).map(serde_json::from_str::<Options>) {
+        None => Options::default(),
+        Some(Ok(options)) => options,
+        Some(Err(_)) => return ptr::null_mut(),
+    };
+    panic::catch_unwind(|| DedupIndex::new(options).ok())
+        .ok()
+        .flatten()
+        .map_or(ptr::null_mut(), |index| Box::into_raw(Box::new(index)))
+}
+
+/// Restores an index from [`dedup_index_serialize`] output. Returns null when
+/// the data is invalid.
+///
+/// # Safety
+/// `data` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dedup_index_deserialize(data: *const c_char) -> *mut DedupIndex {
+    optional_str(data)
+        .and_then(|data| panic::catch_unwind(|| DedupIndex::deserialize(data).ok()).ok()?)
+        .map_or(ptr::null_mut(), |index| Box::into_raw(Box::new(index)))
+}
+
+/// Indexes `text` under `id` and returns `{"duplicateOf": [{"id",
+/// "similarity"}]}` listing earlier near-identical pages, or `{"error"}`.
+///
+/// # Safety
+/// `index` must be a live handle; `id` and `text` valid NUL-terminated
+/// strings. The returned pointer must be released with [`dedup_index_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn dedup_index_add(
+    index: *mut DedupIndex,
+    id: *const c_char,
+    text: *const c_char,
+) -> *mut c_char {
+    catch_panic(|| {
+        let (Some(index), Some(id), Some(text)) =
+            (index.as_mut(), optional_str(id), optional_str(text))
+        else {
+            return into_c_string(json!({ "error": "invalid arguments" }).to_string());
+        };
+        into_c_string(json!({ "duplicateOf": index.add_document(id, text) }).to_string())
+    })
+}
+
+/// Returns `{"matches": [...]}` with up to `limit` indexed pages similar to
+/// `text`, without indexing it, or `{"error"}`.
+///
+/// # Safety
+/// `index` must be a live handle and `text` a valid NUL-terminated string.
+/// The returned pointer must be released with [`dedup_index_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn dedup_index_query(
+    index: *const DedupIndex,
+    text: *const c_char,
+    limit: usize,
+) -> *mut c_char {
+    catch_panic(|| {
+        let (Some(index), Some(text)) = (index.as_ref(), optional_str(text)) else {
+            return into_c_string(json!({ "error": "invalid arguments" }).to_string());
+        };
+        into_c_string(json!({ "matches": index.query_similar(text, limit) }).to_string())
+    })
+}
+
+/// Returns `{"documents", "duplicates"}` for crawl status reporting.
+///
+/// # Safety
+/// `index` must be a live handle. The returned pointer must be released with
+/// [`dedup_index_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn dedup_index_stats(index: *const DedupIndex) -> *mut c_char {
+    catch_panic(|| match index.as_ref() {
+        Some(index) => into_c_string(json!(index.stats()).to_string()),
+        None => into_c_string(json!({ "error": "invalid arguments" }).to_string()),
+    })
+}
+
+/// Serializes the index to a JSON string, e.g. to keep it in Redis between
+/// crawl workers. Returns null for a null handle.
+///
+/// # Safety
+/// `index` must be a live handle or null. The returned pointer must be
+/// released with [`dedup_index_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn dedup_index_serialize(index: *const DedupIndex) -> *mut c_char {
+    catch_panic(|| {
+        index
+            .as_ref()
+            .map_or(ptr::null_mut(), |index| into_c_string(index.serialize()))
+    })
+}
+
+/// Releases an index handle.
+///
+/// # Safety
+/// `index` must come from this library and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn dedup_index_free(index: *mut DedupIndex) {
+    if !index.is_null() {
+        drop(Box::from_raw(index));
+    }
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+/// `ptr` must come from a function of this library and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn dedup_index_free_string(ptr: *mut c_char) {
+    ffi_support::free_string(ptr);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn take(ptr: *mut c_char) -> serde_json::Value {
+        let value = serde_json::from_str(CStr::from_ptr(ptr).to_str().unwrap()).unwrap();
+        dedup_index_free_string(ptr);
+        value
+    }
+
+    #[test]
+    fn drives_an_index_through_the_handle() {
+        unsafe {
+            let index = dedup_index_new(c"{\"shingleSize\": 2}".as_ptr());
+            assert!(!index.is_null());
+            let text = c"the same page body repeated on two urls";
+            take(dedup_index_add(index, c"a".as_ptr(), text.as_ptr()));
+            let added = take(dedup_index_add(index, c"b".as_ptr(), text.as_ptr()));
+            assert_eq!(added["duplicateOf"][0]["id"], "a");
+
+            let serialized = dedup_index_serialize(index);
+            let restored = dedup_index_deserialize(serialized);
+            dedup_index_free_string(serialized);
+            dedup_index_free(index);
+
+            let stats = take(dedup_index_stats(restored));
+            assert_eq!(stats["documents"], 2);
+            assert_eq!(stats["duplicates"], 1);
+            let matches = take(dedup_index_query(restored, text.as_ptr(), 1));
+            assert_eq!(matches["matches"].as_array().unwrap().len(), 1);
+            dedup_index_free(restored);
+
+            assert!(dedup_index_new(c"{\"bands\": 0}".as_ptr()).is_null());
+        }
+    }
+}
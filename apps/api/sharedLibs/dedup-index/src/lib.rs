@@ -0,0 +1,319 @@
+//! Near-duplicate detection across the pages of a crawl. Each page is reduced
+//! to a MinHash signature over word shingles; signatures are bucketed with
+//! LSH banding so a lookup only compares against plausible candidates.
+//! Print views, tag archives and paginated copies of the same article land
+//! well above the default similarity threshold.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::xxh3_64_with_seed;
+
+mod ffi;
+#[cfg(feature = "napi")]
+pub mod node;
+
+pub use ffi::{
+    dedup_index_add, dedup_index_deserialize, dedup_index_free, dedup_index_free_string,
+    dedup_index_new, dedup_index_query, dedup_index_serialize, dedup_index_stats,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DedupError {
+    #[error("bands ({bands}) must divide the signature length ({permutations})")]
+    InvalidBanding { permutations: usize, bands: usize },
+    #[error("invalid serialized index: {0}")]
+    Deserialize(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Options {
+    /// Signature length; more permutations give a tighter estimate.
+    pub permutations: usize,
+    /// LSH bands. With 128 permutations and 16 bands, pages above ~0.7
+    /// similarity are almost always compared.
+    pub bands: usize,
+    /// Words per shingle.
+    pub shingle_size: usize,
+    /// Estimated Jaccard similarity from which a page counts as a duplicate.
+    pub threshold: f64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            permutations: 128,
+            bands: 16,
+            shingle_size: 5,
+            threshold: 0.85,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Match {
+    pub id: String,
+    pub similarity: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    pub documents: usize,
+    /// Documents that matched an earlier one when added.
+    pub duplicates: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Document {
+    id: String,
+    signature: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DedupIndex {
+    options: Options,
+    documents: Vec<Document>,
+    stats: Stats,
+    /// Rebuilt from the signatures on load.
+    #[serde(skip)]
+    buckets: HashMap<(usize, u64), Vec<usize>>,
+}
+
+impl DedupIndex {
+    pub fn new(options: Options) -> Result<Self, DedupError> {
+        validate(&options)?;
+        Ok(DedupIndex {
+            options,
+            documents: Vec::new(),
+            stats: Stats::default(),
+            buckets: HashMap::new(),
+        })
+    }
+
+    /// Adds a page and returns the earlier pages it nearly duplicates, most
+    /// similar first. The page is indexed either way; a page without words
+    /// is counted but never matches.
+    pub fn add_document(&mut self, id: &str, text: &str) -> Vec<Match> {
+        let signature = self.signature(text);
+        let matches = self.matches(&signature, usize::MAX);
+        self.stats.documents += 1;
+        if !matches.is_empty() {
+            self.stats.duplicates += 1;
+        }
+        let index = self.documents.len();
+        for key in self.band_keys(&signature) {
+            self.buckets.entry(key).or_default().push(index);
+        }
+        self.documents.push(Document {
+            id: id.to_string(),
+            signature,
+        });
+        matches
+    }
+
+    /// Indexed pages at or above the threshold, most similar first.
+    pub fn query_similar(&self, text: &str, limit: usize) -> Vec<Match> {
+        self.matches(&self.signature(text), limit)
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    pub fn serialize(&self) -> String {
+        serde_json::to_string(self).expect("index serializes to JSON")
+    }
+
+    pub fn deserialize(data: &str) -> Result<Self, DedupError> {
+        let mut index: DedupIndex = serde_json::from_str(data)?;
+        validate(&index.options)?;
+        for (position, document) in index.documents.iter().enumerate() {
+            for key in index.band_keys(&document.signature) {
+                index.buckets.entry(key).or_default().push(position);
+            }
+        }
+        Ok(index)
+    }
+
+    fn matches(&self, signature: &[u64], limit: usize) -> Vec<Match> {
+        let candidates: HashSet<usize> = self
+            .band_keys(signature)
+            .filter_map(|key| self.buckets.get(&key))
+            .flatten()
+            .copied()
+            .collect();
+        let mut matches: Vec<(usize, f64)> = candidates
+            .into_iter()
+            .map(|index| {
+                (
+                    index,
+                    similarity(signature, &self.documents[index].signature),
+                )
+            })
+            .filter(|(_, similarity)| *similarity >= self.options.threshold)
+            .collect();
+        // Ties broken by insertion order so results are deterministic.
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(index, similarity)| Match {
+                id: self.documents[index].id.clone(),
+                similarity,
+            })
+            .collect()
+    }
+
+    /// Empty for text without words, which puts it in no LSH bucket.
+    fn signature(&self, text: &str) -> Vec<u64> {
+        let shingles = shingles(text, self.options.shingle_size.max(1));
+        if shingles.is_empty() {
+            return Vec::new();
+        }
+        (0..self.options.permutations as u64)
+            .map(|seed| {
+                shingles
+                    .iter()
+                    .map(|shingle| xxh3_64_with_seed(shingle.as_bytes(), seed))
+                    .min()
+                    .unwrap_or(u64::MAX)
+            })
+            .collect()
+    }
+
+    fn band_keys<'a>(&self, signature: &'a [u64]) -> impl Iterator<Item = (usize, u64)> + 'a {
+        let rows = self.options.permutations / self.options.bands;
+        signature.chunks(rows).enumerate().map(|(band, rows)| {
+            let bytes: Vec<u8> = rows.iter().flat_map(|row| row.to_le_bytes()).collect();
+            (band, xxh3_64_with_seed(&bytes, band as u64))
+        })
+    }
+}
+
+fn validate(options: &Options) -> Result<(), DedupError> {
+    if options.bands == 0
+        || options.permutations < options.bands
+        || !options.permutations.is_multiple_of(options.bands)
+    {
+        return Err(DedupError::InvalidBanding {
+            permutations: options.permutations,
+            bands: options.bands,
+        });
+    }
+    Ok(())
+}
+
+/// Lowercased word n-grams. Short texts become a single shingle so they can
+/// still match exact copies.
+fn shingles(text: &str, size: usize) -> HashSet<String> {
+    let words: Vec<String> = text
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        return HashSet::new();
+    }
+    if words.len() <= size {
+        return HashSet::from([words.join(" ")]);
+    }
+    words.windows(size).map(|window| window.join(" ")).collect()
+}
+
+fn similarity(a: &[u64], b: &[u64]) -> f64 {
+    let equal = a.iter().zip(b).filter(|(a, b)| a == b).count();
+    equal as f64 / a.len().max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article(footer: &str) -> String {
+        let body = (0..60)
+            .map(|index| format!("sentence {index} of the article explains a detail"))
+            .collect::<Vec<_>>()
+            .join(". ");
+        format!("{body}. {footer}")
+    }
+
+    #[test]
+    fn detects_near_duplicates() {
+        let mut index = DedupIndex::new(Options::default()).unwrap();
+        assert!(index
+            .add_document("a", &article("Share this post"))
+            .is_empty());
+        let matches = index.add_document("a-print", &article("Printed from example.com"));
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].id, "a");
+        assert!(matches[0].similarity > 0.85);
+
+        let unrelated = "A completely different page about cooking pasta with tomatoes and basil.";
+        assert!(index.add_document("b", unrelated).is_empty());
+        assert_eq!(index.stats().documents, 3);
+        assert_eq!(index.stats().duplicates, 1);
+    }
+
+    #[test]
+    fn queries_without_indexing() {
+        let mut index = DedupIndex::new(Options::default()).unwrap();
+        index.add_document("a", &article(""));
+        index.add_document("b", &article("footer"));
+        let matches = index.query_similar(&article(""), 1);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(
+            matches[0],
+            Match {
+                id: "a".to_string(),
+                similarity: 1.0
+            }
+        );
+        assert_eq!(index.stats().documents, 2);
+    }
+
+    #[test]
+    fn round_trips_through_serialization() {
+        let mut index = DedupIndex::new(Options::default()).unwrap();
+        index.add_document("a", &article(""));
+        let restored = DedupIndex::deserialize(&index.serialize()).unwrap();
+        assert_eq!(restored.query_similar(&article(""), 10)[0].id, "a");
+        assert_eq!(restored.stats().documents, 1);
+        assert!(DedupIndex::deserialize("{").is_err());
+    }
+
+    #[test]
+    fn validates_banding() {
+        let options = Options {
+            bands: 7,
+            ..Options::default()
+        };
+        assert!(matches!(
+            DedupIndex::new(options),
+            Err(DedupError::InvalidBanding { .. })
+        ));
+    }
+
+    #[test]
+    fn matches_short_exact_copies() {
+        let mut index = DedupIndex::new(Options::default()).unwrap();
+        index.add_document("tag", "Tag: rust");
+        assert_eq!(index.query_similar("tag RUST", 10).len(), 1);
+        assert!(index.query_similar("tag: go", 10).is_empty());
+    }
+
+    #[test]
+    fn never_matches_pages_without_words() {
+        let mut index = DedupIndex::new(Options::default()).unwrap();
+        assert!(index.add_document("empty", "").is_empty());
+        assert!(index.add_document("blank", " \n\t ").is_empty());
+        assert!(index.add_document("symbols", "--- | ---").is_empty());
+        assert!(index.query_similar("", 10).is_empty());
+        assert_eq!(index.stats().documents, 3);
+        assert_eq!(index.stats().duplicates, 0);
+
+        let restored = DedupIndex::deserialize(&index.serialize()).unwrap();
+        assert!(restored.query_similar("  ", 10).is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+//! Node-API bindings, built with `--features napi`. Exposes the index as a JS
+//! class. `addDocument` and `querySimilar` return the JSON match array itself
+//! rather than the C ABI's `{"duplicateOf"}`/`{"matches"}` object, and errors
+//! throw.
+
+use napi_derive::napi;
+
+use crate::{DedupIndex, Options};
+
+fn to_napi(err: impl std::fmt::Display) -> napi::Error {
+    napi::Error::from_reason(err.to_string())
+}
+
+#[napi(js_name = "DedupIndex")]
+pub struct JsDedupIndex {
+    inner: DedupIndex,
+}
+
+#[napi]
+impl JsDedupIndex {
+    #[napi(constructor)]
+    pub fn new(options: Option<String>) -> napi::Result<Self> {
+        let options = match options {
+            Some(options) => serde_json::from_str::<Options>(&options).map_err(to_napi)?,
+            None => Options::default(),
+        };
+        Ok(JsDedupIndex {
+            inner: DedupIndex::new(options).map_err(to_napi)?,
+        })
+    }
+
+    #[napi(factory)]
+    pub fn deserialize(data: String) -> napi::Result<Self> {
+        Ok(JsDedupIndex {
+            inner: DedupIndex::deserialize(&data).map_err(to_napi)?,
+        })
+    }
+
+    #[napi]
+    pub fn add_document(&mut self, id: String, text: String) -> napi::Result<String> {
+        serde_json::to_string(&self.inner.add_document(&id, &text)).map_err(to_napi)
+    }
+
+    #[napi]
+    pub fn query_similar(&self, text: String, limit: Option<u32>) -> napi::Result<String> {
+        let limit = limit.map_or(usize::MAX, |limit| limit as usize);
+        serde_json::to_string(&self.inner.query_similar(&text, limit)).map_err(to_napi)
+    }
+
+    #[napi]
+    pub fn stats(&self) -> napi::Result<String> {
+        serde_json::to_string(self.inner.stats()).map_err(to_napi)
+    }
+
+    #[napi]
+    pub fn serialize(&self) -> String {
+        self.inner.serialize()
+    }
+}
@@ -0,0 +1,64 @@
+//! C ABI used by the API through an FFI loader. Results cross the boundary
+//! as JSON strings owned by Rust, which the caller hands back to
+//! [`docx_free_string`].
+
+use std::ffi::c_char;
+
+use ffi_support::{catch_panic, into_c_string};
+use serde_json::json;
+
+use crate::parse_docx;
+
+/// Converts the .docx in `data[..len]` and returns
+/// `{"markdown", "text", "images": [{"name", "contentType", "data"}]}` with
+/// image data base64 encoded, or `{"error"}` when the document can't be read.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes. The returned pointer must be
+/// released with [`docx_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn docx_to_markdown(data: *const u8, len: usize) -> *mut c_char {
+    catch_panic(|| {
+        let bytes = if data.is_null() {
+            &[][..]
+        } else {
+            std::slice::from_raw_parts(data, len)
+        };
+        let result = match parse_docx(bytes) {
+            Ok(parsed) => serde_json::to_string(&parsed),
+            Err(err) => serde_json::to_string(&json!({ "error": err.to_string() })),
+        };
+        into_c_string(result.unwrap_or_else(|err| json!({ "error": err.to_string() }).to_string()))
+    })
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+/// `ptr` must come from a function of this library and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn docx_free_string(ptr: *mut c_char) {
+    ffi_support::free_string(ptr);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn reports_errors_as_json() {
+        let input = b"not a docx";
+        unsafe {
+            let ptr = docx_to_markdown(input.as_ptr(), input.len());
+            let value: serde_json::Value =
+                serde_json::from_str(CStr::from_ptr(ptr).to_str().unwrap()).unwrap();
+            docx_free_string(ptr);
+            assert!(value["error"]
+                .as_str()
+                .unwrap()
+                .contains("invalid docx archive"));
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Converts Office Open XML word processing documents (.docx) into markdown
+//! and plain text, keeping headings, lists and tables, and pulling out the
+//! embedded images so crawls can treat Office documents like PDFs.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use zip_package::{image_content_type, serialize_base64, Package, PackageError};
+
+mod ffi;
+#[cfg(feature = "napi")]
+pub mod node;
+mod parts;
+mod render;
+
+pub use ffi::{docx_free_string, docx_to_markdown};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DocxError {
+    #[error("invalid docx archive: {0}")]
+    Archive(#[from] PackageError),
+    #[error("malformed xml in {part}: {source}")]
+    Xml {
+        part: String,
+        source: roxmltree::Error,
+    },
+    #[error("missing {0}, not a word processing document")]
+    MissingPart(String),
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ParsedDocx {
+    pub markdown: String,
+    pub text: String,
+    pub images: Vec<EmbeddedImage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedImage {
+    /// Path of the image inside the package, also used as the markdown target.
+    pub name: String,
+    pub content_type: String,
+    #[serde(serialize_with = "serialize_base64")]
+    pub data: Vec<u8>,
+}
+
+/// Parses a .docx file from memory.
+pub fn parse_docx(data: &[u8]) -> Result<ParsedDocx, DocxError> {
+    let mut package = Package::open(data)?;
+
+    let document = package
+        .read_string("word/document.xml")?
+        .ok_or_else(|| DocxError::MissingPart("word/document.xml".to_string()))?;
+    let relationships = match package.read_string("word/_rels/document.xml.rels")? {
+        Some(xml) => parts::parse_relationships(&xml, "word/")?,
+        None => HashMap::new(),
+    };
+    let numbering = match package.read_string("word/numbering.xml")? {
+        Some(xml) => parts::parse_numbering(&xml)?,
+        None => HashMap::new(),
+    };
+    let styles = match package.read_string("word/styles.xml")? {
+        Some(xml) => parts::parse_heading_styles(&xml)?,
+        None => HashMap::new(),
+    };
+
+    let context = render::Context {
+        relationships: &relationships,
+        numbering: &numbering,
+        heading_styles: &styles,
+    };
+    let rendered = render::render_document(&document, &context)?;
+
+    let mut images = Vec::with_capacity(rendered.images.len());
+    for name in rendered.images {
+        if let Some(data) = package.read_bytes(&name)? {
+            images.push(EmbeddedImage {
+                content_type: image_content_type(&name).to_string(),
+                name,
+                data,
+            });
+        }
+    }
+
+    Ok(ParsedDocx {
+        markdown: rendered.markdown,
+        text: rendered.text,
+        images,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+
+    pub(crate) fn build_docx(document: &str, extra: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+        writer.start_file("word/document.xml", options).unwrap();
+        writer
+            .write_all(
+                format!(
+                    r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"><w:body>{document}</w:body></w:document>"#
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        for (name, data) in extra {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn converts_headings_and_formatting() {
+        let docx = build_docx(
+            r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Title</w:t></w:r></w:p>
+               <w:p><w:r><w:t xml:space="preserve">Plain </w:t></w:r><w:r><w:rPr><w:b/></w:rPr><w:t>bold</w:t></w:r><w:r><w:rPr><w:b/></w:rPr><w:t xml:space="preserve"> still</w:t></w:r></w:p>"#,
+            &[],
+        );
+        let parsed = parse_docx(&docx).unwrap();
+        assert_eq!(parsed.markdown, "# Title\n\nPlain **bold still**\n");
+        assert_eq!(parsed.text, "Title\nPlain bold still\n");
+    }
+
+    #[test]
+    fn resolves_heading_styles_by_name() {
+        let styles = br#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:styleId="Berschrift2"><w:name w:val="heading 2"/></w:style></w:styles>"#;
+        let docx = build_docx(
+            r#"<w:p><w:pPr><w:pStyle w:val="Berschrift2"/></w:pPr><w:r><w:t>Kapitel</w:t></w:r></w:p>"#,
+            &[("word/styles.xml", styles)],
+        );
+        assert_eq!(parse_docx(&docx).unwrap().markdown, "## Kapitel\n");
+    }
+
+    #[test]
+    fn ignores_out_of_range_levels() {
+        let styles = br#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:styleId="Deep"><w:name w:val="Deep"/><w:pPr><w:outlineLvl w:val="255"/></w:pPr></w:style></w:styles>"#;
+        let docx = build_docx(
+            r#"<w:p><w:pPr><w:pStyle w:val="Deep"/></w:pPr><w:r><w:t>Styled</w:t></w:r></w:p>
+               <w:p><w:pPr><w:outlineLvl w:val="255"/></w:pPr><w:r><w:t>Outline</w:t></w:r></w:p>"#,
+            &[("word/styles.xml", styles)],
+        );
+        assert_eq!(parse_docx(&docx).unwrap().markdown, "Styled\n\nOutline\n");
+
+        let docx = build_docx(
+            r#"<w:p><w:pPr><w:numPr><w:ilvl w:val="200"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>Deep</w:t></w:r></w:p>"#,
+            &[],
+        );
+        assert_eq!(
+            parse_docx(&docx).unwrap().markdown,
+            format!("{}- Deep\n", "  ".repeat(8))
+        );
+    }
+
+    #[test]
+    fn converts_lists_using_numbering_definitions() {
+        let numbering = br#"<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+            <w:abstractNum w:abstractNumId="0"><w:lvl w:ilvl="0"><w:numFmt w:val="bullet"/></w:lvl><w:lvl w:ilvl="1"><w:numFmt w:val="decimal"/></w:lvl></w:abstractNum>
+            <w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num></w:numbering>"#;
+        let item = |level: u8, text: &str| {
+            format!(
+                r#"<w:p><w:pPr><w:numPr><w:ilvl w:val="{level}"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>{text}</w:t></w:r></w:p>"#
+            )
+        };
+        let docx = build_docx(
+            &format!("{}{}{}", item(0, "One"), item(1, "Nested"), item(0, "Two")),
+            &[("word/numbering.xml", numbering)],
+        );
+        assert_eq!(
+            parse_docx(&docx).unwrap().markdown,
+            "- One\n  1. Nested\n- Two\n"
+        );
+    }
+
+    #[test]
+    fn converts_tables() {
+        let cell = |text: &str| format!("<w:tc><w:p><w:r><w:t>{text}</w:t></w:r></w:p></w:tc>");
+        let docx = build_docx(
+            &format!(
+                "<w:tbl><w:tr>{}{}</w:tr><w:tr>{}{}</w:tr></w:tbl>",
+                cell("Name"),
+                cell("Value"),
+                cell("a|b"),
+                cell("1")
+            ),
+            &[],
+        );
+        let parsed = parse_docx(&docx).unwrap();
+        assert_eq!(
+            parsed.markdown,
+            "| Name | Value |\n| --- | --- |\n| a\\|b | 1 |\n"
+        );
+        assert_eq!(parsed.text, "Name\tValue\na|b\t1\n");
+    }
+
+    #[test]
+    fn extracts_links_and_images() {
+        let rels = br#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+            <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://firecrawl.dev" TargetMode="External"/>
+            <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/image1.png"/></Relationships>"#;
+        let docx = build_docx(
+            r#"<w:p><w:hyperlink r:id="rId1"><w:r><w:t>Firecrawl</w:t></w:r></w:hyperlink></w:p>
+               <w:p><w:r><w:drawing><wp:inline><wp:docPr id="1" name="Picture 1" descr="Logo"/><a:graphic><a:graphicData><a:blip r:embed="rId2"/></a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>"#,
+            &[
+                ("word/_rels/document.xml.rels", rels),
+                ("word/media/image1.png", b"\x89PNG"),
+            ],
+        );
+        let parsed = parse_docx(&docx).unwrap();
+        assert_eq!(
+            parsed.markdown,
+            "[Firecrawl](https://firecrawl.dev)\n\n![Logo](word/media/image1.png)\n"
+        );
+        assert_eq!(parsed.images.len(), 1);
+        assert_eq!(parsed.images[0].name, "word/media/image1.png");
+        assert_eq!(parsed.images[0].content_type, "image/png");
+        assert_eq!(parsed.images[0].data, b"\x89PNG");
+    }
+
+    #[test]
+    fn rejects_non_docx_archives() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file("hello.txt", SimpleFileOptions::default())
+            .unwrap();
+        let archive = writer.finish().unwrap().into_inner();
+        assert!(matches!(
+            parse_docx(&archive),
+            Err(DocxError::MissingPart(_))
+        ));
+        assert!(matches!(parse_docx(b"nope"), Err(DocxError::Archive(_))));
+    }
+}
@@ -0,0 +1,14 @@
+//! Node-API bindings, built with `--features napi`.
+
+use napi::bindgen_prelude::Buffer;
+use napi_derive::napi;
+
+use crate::parse_docx;
+
+/// Returns the parsed document as the same JSON string as the C ABI, but
+/// throws on failure instead of returning `{"error"}`.
+#[napi(js_name = "docxToMarkdown")]
+pub fn docx_to_markdown(data: Buffer) -> napi::Result<String> {
+    let parsed = parse_docx(&data).map_err(|err| napi::Error::from_reason(err.to_string()))?;
+    serde_json::to_string(&parsed).map_err(|err| napi::Error::from_reason(err.to_string()))
+}
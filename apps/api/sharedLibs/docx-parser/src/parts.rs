@@ -0,0 +1,149 @@
+//! Readers for the auxiliary package parts referenced from document.xml.
+
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node};
+use zip_package::resolve_part_path;
+
+use crate::DocxError;
+
+pub(crate) const W_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+
+#[derive(Debug)]
+pub(crate) struct Relationship {
+    /// Package path for internal targets, the raw URL for external ones.
+    pub target: String,
+    pub external: bool,
+}
+
+/// Resolves a `w:val`-style attribute in the wordprocessingml namespace.
+pub(crate) fn w_attr<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attribute((W_NS, name))
+}
+
+pub(crate) fn w_child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| is_w(*child, name))
+}
+
+pub(crate) fn is_w(node: Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name && node.tag_name().namespace() == Some(W_NS)
+}
+
+fn parse_xml<'a>(xml: &'a str, part: &str) -> Result<Document<'a>, DocxError> {
+    Document::parse(xml).map_err(|source| DocxError::Xml {
+        part: part.to_string(),
+        source,
+    })
+}
+
+pub(crate) fn parse_relationships(
+    xml: &str,
+    base: &str,
+) -> Result<HashMap<String, Relationship>, DocxError> {
+    let doc = parse_xml(xml, "relationships")?;
+    let mut relationships = HashMap::new();
+    for rel in doc
+        .descendants()
+        .filter(|node| node.has_tag_name("Relationship"))
+    {
+        let (Some(id), Some(target)) = (rel.attribute("Id"), rel.attribute("Target")) else {
+            continue;
+        };
+        let external = rel.attribute("TargetMode") == Some("External");
+        let target = if external {
+            target.to_string()
+        } else {
+            resolve_part_path(base, target)
+        };
+        relationships.insert(id.to_string(), Relationship { target, external });
+    }
+    Ok(relationships)
+}
+
+/// Maps `(numId, ilvl)` to whether that list level is numbered (as opposed to
+/// bulleted).
+pub(crate) fn parse_numbering(xml: &str) -> Result<HashMap<(String, u8), bool>, DocxError> {
+    let doc = parse_xml(xml, "word/numbering.xml")?;
+    let root = doc.root_element();
+
+    let mut abstract_levels: HashMap<&str, HashMap<u8, bool>> = HashMap::new();
+    for abstract_num in root.children().filter(|n| is_w(*n, "abstractNum")) {
+        let Some(id) = w_attr(abstract_num, "abstractNumId") else {
+            continue;
+        };
+        let levels = abstract_num
+            .children()
+            .filter(|n| is_w(*n, "lvl"))
+            .filter_map(|lvl| {
+                let level = w_attr(lvl, "ilvl")?.parse().ok()?;
+                let format = w_child(lvl, "numFmt").and_then(|f| w_attr(f, "val"));
+                Some((
+                    level,
+                    !matches!(format, Some("bullet") | Some("none") | None),
+                ))
+            })
+            .collect();
+        abstract_levels.insert(id, levels);
+    }
+
+    let mut numbering = HashMap::new();
+    for num in root.children().filter(|n| is_w(*n, "num")) {
+        let Some(num_id) = w_attr(num, "numId") else {
+            continue;
+        };
+        let Some(levels) = w_child(num, "abstractNumId")
+            .and_then(|n| w_attr(n, "val"))
+            .and_then(|id| abstract_levels.get(id))
+        else {
+            continue;
+        };
+        for (level, ordered) in levels {
+            numbering.insert((num_id.to_string(), *level), *ordered);
+        }
+    }
+    Ok(numbering)
+}
+
+/// Maps paragraph style ids to heading levels. Style ids are localized
+/// ("Berschrift1" in German templates) but the style names are not.
+pub(crate) fn parse_heading_styles(xml: &str) -> Result<HashMap<String, u8>, DocxError> {
+    let doc = parse_xml(xml, "word/styles.xml")?;
+    let mut styles = HashMap::new();
+    for style in doc.root_element().children().filter(|n| is_w(*n, "style")) {
+        let Some(id) = w_attr(style, "styleId") else {
+            continue;
+        };
+        let name = w_child(style, "name")
+            .and_then(|n| w_attr(n, "val"))
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let outline_level = w_child(style, "pPr")
+            .and_then(|ppr| w_child(ppr, "outlineLvl"))
+            .and_then(|n| w_attr(n, "val"))
+            .and_then(|val| val.parse::<u8>().ok());
+
+        let level = if name == "title" {
+            Some(1)
+        } else if let Some(level) = name.strip_prefix("heading ") {
+            level.parse().ok()
+        } else {
+            outline_level.and_then(|level| level.checked_add(1))
+        };
+        if let Some(level) = level.filter(|level| (1..=6).contains(level)) {
+            styles.insert(id.to_string(), level);
+        }
+    }
+    Ok(styles)
+}
+
+/// Fallback for documents without styles.xml, which still use the built-in
+/// English style ids.
+pub(crate) fn builtin_heading_level(style_id: &str) -> Option<u8> {
+    if style_id.eq_ignore_ascii_case("title") {
+        return Some(1);
+    }
+    let level = style_id
+        .strip_prefix("Heading")
+        .or_else(|| style_id.strip_prefix("heading"))?;
+    level.parse().ok().filter(|level| (1..=6).contains(level))
+}
@@ -0,0 +1,395 @@
+//! Walks word/document.xml and renders the body as markdown and plain text.
+
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node};
+
+use crate::parts::{builtin_heading_level, is_w, w_attr, w_child, Relationship};
+use crate::DocxError;
+
+const R_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+pub(crate) struct Context<'a> {
+    pub relationships: &'a HashMap<String, Relationship>,
+    pub numbering: &'a HashMap<(String, u8), bool>,
+    pub heading_styles: &'a HashMap<String, u8>,
+}
+
+pub(crate) struct Rendered {
+    pub markdown: String,
+    pub text: String,
+    /// Package paths of the images referenced from the body, in order.
+    pub images: Vec<String>,
+}
+
+enum Block {
+    ListItem { markdown: String, text: String },
+    Other { markdown: String, text: String },
+}
+
+enum Segment {
+    Text {
+        text: String,
+        bold: bool,
+        italic: bool,
+    },
+    /// Already formatted markdown (links, images) with its plain text.
+    Raw { markdown: String, text: String },
+}
+
+pub(crate) fn render_document(xml: &str, context: &Context) -> Result<Rendered, DocxError> {
+    let doc = Document::parse(xml).map_err(|source| DocxError::Xml {
+        part: "word/document.xml".to_string(),
+        source,
+    })?;
+    let body = doc
+        .descendants()
+        .find(|node| is_w(*node, "body"))
+        .ok_or_else(|| DocxError::MissingPart("w:body".to_string()))?;
+
+    let mut renderer = Renderer {
+        context,
+        images: Vec::new(),
+    };
+    let mut blocks = Vec::new();
+    renderer.render_blocks(body, &mut blocks);
+
+    let mut markdown = String::new();
+    let mut text = String::new();
+    let mut previous_was_list = false;
+    for block in blocks {
+        let (block_markdown, block_text, is_list) = match block {
+            Block::ListItem { markdown, text } => (markdown, text, true),
+            Block::Other { markdown, text } => (markdown, text, false),
+        };
+        if !markdown.is_empty() {
+            markdown.push_str(if is_list && previous_was_list {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+        markdown.push_str(&block_markdown);
+        text.push_str(&block_text);
+        text.push('\n');
+        previous_was_list = is_list;
+    }
+    if !markdown.is_empty() {
+        markdown.push('\n');
+    }
+
+    Ok(Rendered {
+        markdown,
+        text,
+        images: renderer.images,
+    })
+}
+
+struct Renderer<'a> {
+    context: &'a Context<'a>,
+    images: Vec<String>,
+}
+
+impl Renderer<'_> {
+    fn render_blocks(&mut self, parent: Node, blocks: &mut Vec<Block>) {
+        for child in parent.children().filter(Node::is_element) {
+            if is_w(child, "p") {
+                if let Some(block) = self.render_paragraph(child) {
+                    blocks.push(block);
+                }
+            } else if is_w(child, "tbl") {
+                if let Some(block) = self.render_table(child) {
+                    blocks.push(block);
+                }
+            } else if is_w(child, "sdt") {
+                if let Some(content) = w_child(child, "sdtContent") {
+                    self.render_blocks(content, blocks);
+                }
+            } else if is_w(child, "customXml") || is_w(child, "ins") {
+                self.render_blocks(child, blocks);
+            }
+        }
+    }
+
+    fn render_paragraph(&mut self, paragraph: Node) -> Option<Block> {
+        let mut segments = Vec::new();
+        self.collect_segments(paragraph, false, false, &mut segments);
+        let (inline_markdown, inline_text) = join_segments(&segments);
+        let inline_markdown = inline_markdown.trim();
+        if inline_markdown.is_empty() {
+            return None;
+        }
+        let text = inline_text.trim().to_string();
+
+        let properties = w_child(paragraph, "pPr");
+        if let Some(level) = properties.and_then(|p| self.heading_level(p)) {
+            return Some(Block::Other {
+                markdown: format!("{} {}", "#".repeat(level as usize), inline_markdown),
+                text,
+            });
+        }
+
+        if let Some(numbering) = properties.and_then(|p| w_child(p, "numPr")) {
+            // OOXML allows levels 0 to 8; anything else is clamped rather than
+            // trusted as an indent width
+            let level = w_child(numbering, "ilvl")
+                .and_then(|n| w_attr(n, "val"))
+                .and_then(|val| val.parse::<u8>().ok())
+                .unwrap_or(0)
+                .min(8);
+            let num_id = w_child(numbering, "numId").and_then(|n| w_attr(n, "val"));
+            // numId 0 explicitly removes numbering inherited from the style
+            if let Some(num_id) = num_id.filter(|id| *id != "0") {
+                let ordered = self
+                    .context
+                    .numbering
+                    .get(&(num_id.to_string(), level))
+                    .copied()
+                    .unwrap_or(false);
+                let marker = if ordered { "1." } else { "-" };
+                return Some(Block::ListItem {
+                    markdown: format!(
+                        "{}{} {}",
+                        "  ".repeat(level as usize),
+                        marker,
+                        inline_markdown
+                    ),
+                    text,
+                });
+            }
+        }
+
+        Some(Block::Other {
+            markdown: inline_markdown.to_string(),
+            text,
+        })
+    }
+
+    fn heading_level(&self, properties: Node) -> Option<u8> {
+        if let Some(style) = w_child(properties, "pStyle").and_then(|n| w_attr(n, "val")) {
+            let level = self
+                .context
+                .heading_styles
+                .get(style)
+                .copied()
+                .or_else(|| builtin_heading_level(style));
+            if level.is_some() {
+                return level;
+            }
+        }
+        w_child(properties, "outlineLvl")
+            .and_then(|n| w_attr(n, "val"))
+            .and_then(|val| val.parse::<u8>().ok())
+            .and_then(|level| level.checked_add(1))
+            .filter(|level| (1..=6).contains(level))
+    }
+
+    fn collect_segments(&mut self, parent: Node, bold: bool, italic: bool, out: &mut Vec<Segment>) {
+        for child in parent.children().filter(Node::is_element) {
+            if is_w(child, "r") {
+                let properties = w_child(child, "rPr");
+                let bold = bold || properties.is_some_and(|p| toggle_enabled(p, "b"));
+                let italic = italic || properties.is_some_and(|p| toggle_enabled(p, "i"));
+                self.collect_run(child, bold, italic, out);
+            } else if is_w(child, "hyperlink") {
+                let mut inner = Vec::new();
+                self.collect_segments(child, bold, italic, &mut inner);
+                let (markdown, text) = join_segments(&inner);
+                let target = child
+                    .attribute((R_NS, "id"))
+                    .and_then(|id| self.context.relationships.get(id))
+                    .map(|rel| rel.target.clone())
+                    .or_else(|| w_attr(child, "anchor").map(|anchor| format!("#{anchor}")));
+                match target {
+                    Some(target) if !markdown.trim().is_empty() => out.push(Segment::Raw {
+                        markdown: format!("[{}]({})", markdown.trim(), target),
+                        text,
+                    }),
+                    _ => out.extend(inner),
+                }
+            } else if is_w(child, "ins")
+                || is_w(child, "smartTag")
+                || is_w(child, "fldSimple")
+                || is_w(child, "customXml")
+            {
+                self.collect_segments(child, bold, italic, out);
+            } else if is_w(child, "sdt") {
+                if let Some(content) = w_child(child, "sdtContent") {
+                    self.collect_segments(content, bold, italic, out);
+                }
+            }
+        }
+    }
+
+    fn collect_run(&mut self, run: Node, bold: bool, italic: bool, out: &mut Vec<Segment>) {
+        let push_text = |text: &str, out: &mut Vec<Segment>| {
+            out.push(Segment::Text {
+                text: text.to_string(),
+                bold,
+                italic,
+            })
+        };
+        for child in run.children().filter(Node::is_element) {
+            if is_w(child, "t") {
+                push_text(child.text().unwrap_or_default(), out);
+            } else if is_w(child, "tab") {
+                push_text("\t", out);
+            } else if is_w(child, "br") || is_w(child, "cr") {
+                push_text("\n", out);
+            } else if is_w(child, "drawing") || is_w(child, "pict") {
+                if let Some(image) = self.collect_image(child) {
+                    out.push(image);
+                }
+            }
+        }
+    }
+
+    fn collect_image(&mut self, drawing: Node) -> Option<Segment> {
+        // DrawingML uses a:blip/@r:embed, legacy VML uses v:imagedata/@r:id
+        let relationship = drawing
+            .descendants()
+            .find_map(|node| match node.tag_name().name() {
+                "blip" => node.attribute((R_NS, "embed")),
+                "imagedata" => node.attribute((R_NS, "id")),
+                _ => None,
+            })?;
+        let target = self.context.relationships.get(relationship)?;
+        if target.external {
+            return None;
+        }
+        let alt = drawing
+            .descendants()
+            .find(|node| node.tag_name().name() == "docPr")
+            .and_then(|node| node.attribute("descr"))
+            .unwrap_or_default();
+        if !self.images.contains(&target.target) {
+            self.images.push(target.target.clone());
+        }
+        Some(Segment::Raw {
+            markdown: format!("![{}]({})", alt, target.target),
+            text: String::new(),
+        })
+    }
+
+    fn render_table(&mut self, table: Node) -> Option<Block> {
+        let mut rows: Vec<Vec<(String, String)>> = Vec::new();
+        for row in table.children().filter(|n| is_w(*n, "tr")) {
+            let cells = row
+                .children()
+                .filter(|n| is_w(*n, "tc"))
+                .map(|cell| self.render_cell(cell))
+                .collect();
+            rows.push(cells);
+        }
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return None;
+        }
+
+        let mut markdown_rows = Vec::with_capacity(rows.len() + 1);
+        let mut text_rows = Vec::with_capacity(rows.len());
+        for (index, row) in rows.iter().enumerate() {
+            let mut cells: Vec<&str> = row.iter().map(|(markdown, _)| markdown.as_str()).collect();
+            cells.resize(columns, "");
+            markdown_rows.push(format!("| {} |", cells.join(" | ")));
+            if index == 0 {
+                markdown_rows.push(format!("|{}", " --- |".repeat(columns)));
+            }
+            text_rows.push(
+                row.iter()
+                    .map(|(_, text)| text.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\t"),
+            );
+        }
+        Some(Block::Other {
+            markdown: markdown_rows.join("\n"),
+            text: text_rows.join("\n"),
+        })
+    }
+
+    fn render_cell(&mut self, cell: Node) -> (String, String) {
+        let mut markdown = Vec::new();
+        let mut text = Vec::new();
+        for paragraph in cell.descendants().filter(|n| is_w(*n, "p")) {
+            let mut segments = Vec::new();
+            self.collect_segments(paragraph, false, false, &mut segments);
+            let (paragraph_markdown, paragraph_text) = join_segments(&segments);
+            if !paragraph_markdown.trim().is_empty() {
+                markdown.push(paragraph_markdown.trim().to_string());
+                text.push(paragraph_text.trim().to_string());
+            }
+        }
+        let markdown = markdown.join(" ").replace('|', "\\|").replace('\n', " ");
+        (markdown, text.join(" ").replace('\n', " "))
+    }
+}
+
+/// Toggle properties such as `<w:b/>` are on unless explicitly switched off.
+fn toggle_enabled(properties: Node, name: &str) -> bool {
+    w_child(properties, name)
+        .is_some_and(|node| !matches!(w_attr(node, "val"), Some("0" | "false" | "off")))
+}
+
+/// Renders segments, merging adjacent runs with identical formatting so Word's
+/// habit of splitting runs doesn't leave `**a****b**` behind.
+fn join_segments(segments: &[Segment]) -> (String, String) {
+    let mut markdown = String::new();
+    let mut text = String::new();
+    let mut pending: Option<(String, bool, bool)> = None;
+
+    let flush = |pending: &mut Option<(String, bool, bool)>, markdown: &mut String| {
+        if let Some((run, bold, italic)) = pending.take() {
+            markdown.push_str(&emphasize(&run, bold, italic));
+        }
+    };
+
+    for segment in segments {
+        match segment {
+            Segment::Text {
+                text: run,
+                bold,
+                italic,
+            } => {
+                text.push_str(run);
+                match &mut pending {
+                    Some((pending_run, pending_bold, pending_italic))
+                        if pending_bold == bold && pending_italic == italic =>
+                    {
+                        pending_run.push_str(run)
+                    }
+                    _ => {
+                        flush(&mut pending, &mut markdown);
+                        pending = Some((run.clone(), *bold, *italic));
+                    }
+                }
+            }
+            Segment::Raw {
+                markdown: raw,
+                text: raw_text,
+            } => {
+                flush(&mut pending, &mut markdown);
+                markdown.push_str(raw);
+                text.push_str(raw_text);
+            }
+        }
+    }
+    flush(&mut pending, &mut markdown);
+    (markdown, text)
+}
+
+fn emphasize(run: &str, bold: bool, italic: bool) -> String {
+    let marker = match (bold, italic) {
+        (true, true) => "***",
+        (true, false) => "**",
+        (false, true) => "*",
+        (false, false) => return run.to_string(),
+    };
+    let trimmed = run.trim();
+    if trimmed.is_empty() {
+        return run.to_string();
+    }
+    let leading = &run[..run.len() - run.trim_start().len()];
+    let trailing = &run[run.trim_end().len()..];
+    format!("{leading}{marker}{trimmed}{marker}{trailing}")
+}
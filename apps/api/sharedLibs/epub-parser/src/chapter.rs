@@ -0,0 +1,63 @@
+//! Content documents to markdown.
+
+use crate::resolve_path;
+
+/// Converts one XHTML content document. Only the body is converted so the
+/// `<title>` and inline stylesheets don't leak into the text, and image
+/// sources are rewritten to package paths so they match [`crate::Cover`] and
+/// the zip entries.
+pub(crate) fn html_to_markdown(html: &str, directory: &str) -> String {
+    let body = body_of(html);
+    let body = rewrite_attribute(&body, "src=\"", directory);
+    let body = rewrite_attribute(&body, "xlink:href=\"", directory);
+    html2md::parse_html(&body).trim().to_string()
+}
+
+fn body_of(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let start = lower
+        .find("<body")
+        .and_then(|open| lower[open..].find('>').map(|end| open + end + 1));
+    let end = lower.rfind("</body");
+    match (start, end) {
+        (Some(start), Some(end)) if start <= end => html[start..end].to_string(),
+        (Some(start), None) => html[start..].to_string(),
+        _ => html.to_string(),
+    }
+}
+
+fn rewrite_attribute(html: &str, attribute: &str, directory: &str) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(position) = rest.find(attribute) {
+        let value_start = position + attribute.len();
+        output.push_str(&rest[..value_start]);
+        rest = &rest[value_start..];
+        let Some(value_end) = rest.find('"') else {
+            break;
+        };
+        let value = &rest[..value_end];
+        if value.contains("://") || value.starts_with("data:") || value.starts_with('#') {
+            output.push_str(value);
+        } else {
+            output.push_str(&resolve_path(directory, value));
+        }
+        rest = &rest[value_end..];
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_external_and_inline_sources() {
+        let html = r#"<body><img src="https://example.com/a.png"/><img src="data:image/png;base64,AA"/><img src="b.png"/></body>"#;
+        assert_eq!(
+            rewrite_attribute(body_of(html).as_str(), "src=\"", "OEBPS"),
+            r#"<img src="https://example.com/a.png"/><img src="data:image/png;base64,AA"/><img src="OEBPS/b.png"/>"#
+        );
+    }
+}
@@ -0,0 +1,494 @@
+//! Converts EPUB books into chapter-ordered markdown, together with the
+//! package metadata and the cover image.
+
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node};
+use serde::Serialize;
+use zip_package::{resolve_part_path, serialize_base64, Package, PackageError};
+
+mod chapter;
+mod ffi;
+#[cfg(feature = "napi")]
+pub mod node;
+
+pub use ffi::{epub_free_string, epub_to_markdown};
+
+const OPF_NS: &str = "http://www.idpf.org/2007/opf";
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+
+#[derive(Debug, thiserror::Error)]
+pub enum EpubError {
+    #[error("invalid epub archive: {0}")]
+    Archive(#[from] PackageError),
+    #[error("malformed xml in {part}: {source}")]
+    Xml {
+        part: String,
+        source: roxmltree::Error,
+    },
+    #[error("missing {0}, not an epub")]
+    MissingPart(String),
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ParsedEpub {
+    pub metadata: Metadata,
+    /// All chapters in spine order.
+    pub markdown: String,
+    pub chapters: Vec<Chapter>,
+    pub cover: Option<Cover>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Metadata {
+    pub title: Option<String>,
+    pub creators: Vec<String>,
+    pub language: Option<String>,
+    pub identifier: Option<String>,
+    pub publisher: Option<String>,
+    pub date: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Chapter {
+    /// Package path of the content document.
+    pub href: String,
+    /// Label from the table of contents, if the chapter is listed there.
+    pub title: Option<String>,
+    pub markdown: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cover {
+    pub name: String,
+    pub content_type: String,
+    #[serde(serialize_with = "serialize_base64")]
+    pub data: Vec<u8>,
+}
+
+struct ManifestItem {
+    href: String,
+    media_type: String,
+    properties: String,
+}
+
+impl ManifestItem {
+    fn has_property(&self, property: &str) -> bool {
+        self.properties.split_whitespace().any(|p| p == property)
+    }
+}
+
+/// Manifest items in document order, so lookups by property pick the same
+/// item on every run, plus an index by id.
+struct Manifest<'a> {
+    items: Vec<ManifestItem>,
+    ids: HashMap<&'a str, usize>,
+}
+
+impl Manifest<'_> {
+    fn get(&self, id: &str) -> Option<&ManifestItem> {
+        self.ids.get(id).map(|&index| &self.items[index])
+    }
+
+    fn find(&self, predicate: impl Fn(&ManifestItem) -> bool) -> Option<&ManifestItem> {
+        self.items.iter().find(|item| predicate(item))
+    }
+}
+
+/// Parses an .epub file from memory.
+pub fn parse_epub(data: &[u8]) -> Result<ParsedEpub, EpubError> {
+    let mut package = Package::open(data)?;
+
+    let container = package
+        .read_string("META-INF/container.xml")?
+        .ok_or_else(|| EpubError::MissingPart("META-INF/container.xml".to_string()))?;
+    let opf_path = parse_xml(&container, "META-INF/container.xml")?
+        .descendants()
+        .find(|node| node.tag_name().name() == "rootfile")
+        .and_then(|node| node.attribute("full-path"))
+        .map(str::to_string)
+        .ok_or_else(|| EpubError::MissingPart("rootfile".to_string()))?;
+    let opf = package
+        .read_string(&opf_path)?
+        .ok_or_else(|| EpubError::MissingPart(opf_path.clone()))?;
+    let opf_doc = parse_xml(&opf, &opf_path)?;
+    let opf_dir = directory_of(&opf_path);
+
+    let mut manifest = Manifest {
+        items: Vec::new(),
+        ids: HashMap::new(),
+    };
+    for item in opf_doc
+        .descendants()
+        .filter(|node| is(*node, OPF_NS, "item"))
+    {
+        let (Some(id), Some(href)) = (item.attribute("id"), item.attribute("href")) else {
+            continue;
+        };
+        // The first item wins when ids repeat
+        if manifest.ids.contains_key(id) {
+            continue;
+        }
+        manifest.ids.insert(id, manifest.items.len());
+        manifest.items.push(ManifestItem {
+            href: resolve_path(opf_dir, href),
+            media_type: item.attribute("media-type").unwrap_or_default().to_string(),
+            properties: item.attribute("properties").unwrap_or_default().to_string(),
+        });
+    }
+
+    let toc = read_toc(&mut package, &opf_doc, &manifest);
+
+    let mut chapters = Vec::new();
+    let spine = opf_doc
+        .descendants()
+        .filter(|node| is(*node, OPF_NS, "itemref"))
+        .filter_map(|itemref| manifest.get(itemref.attribute("idref")?));
+    for item in spine {
+        if !item.media_type.contains("html") {
+            continue;
+        }
+        let Some(html) = package.read_string(&item.href)? else {
+            continue;
+        };
+        let markdown = chapter::html_to_markdown(&html, directory_of(&item.href));
+        if markdown.is_empty() {
+            continue;
+        }
+        chapters.push(Chapter {
+            title: toc.get(&item.href).cloned(),
+            href: item.href.clone(),
+            markdown,
+        });
+    }
+
+    let cover = match find_cover(&opf_doc, &manifest) {
+        Some(item) => package.read_bytes(&item.href)?.map(|data| Cover {
+            name: item.href.clone(),
+            content_type: item.media_type.clone(),
+            data,
+        }),
+        None => None,
+    };
+
+    let mut markdown = chapters
+        .iter()
+        .map(|chapter| chapter.markdown.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    if !markdown.is_empty() {
+        markdown.push('\n');
+    }
+
+    Ok(ParsedEpub {
+        metadata: read_metadata(&opf_doc),
+        markdown,
+        chapters,
+        cover,
+    })
+}
+
+fn read_metadata(opf: &Document) -> Metadata {
+    let values = |name: &str| -> Vec<String> {
+        opf.descendants()
+            .filter(|node| is(*node, DC_NS, name))
+            .filter_map(|node| node.text())
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect()
+    };
+    let first = |name: &str| values(name).into_iter().next();
+    Metadata {
+        title: first("title"),
+        creators: values("creator"),
+        language: first("language"),
+        identifier: first("identifier"),
+        publisher: first("publisher"),
+        date: first("date"),
+        description: first("description"),
+    }
+}
+
+/// EPUB 3 flags the cover in the manifest, EPUB 2 points at it from a
+/// `<meta name="cover">` element.
+fn find_cover<'a>(opf: &Document, manifest: &'a Manifest) -> Option<&'a ManifestItem> {
+    manifest
+        .find(|item| item.has_property("cover-image"))
+        .or_else(|| {
+            let id = opf
+                .descendants()
+                .find(|node| is(*node, OPF_NS, "meta") && node.attribute("name") == Some("cover"))?
+                .attribute("content")?;
+            manifest.get(id)
+        })
+        .filter(|item| item.media_type.starts_with("image/"))
+}
+
+/// Maps content document paths to their table of contents labels, from the
+/// EPUB 3 nav document or, failing that, the EPUB 2 NCX. A malformed TOC only
+/// costs the chapter titles, so errors are swallowed here.
+fn read_toc(package: &mut Package, opf: &Document, manifest: &Manifest) -> HashMap<String, String> {
+    let nav = manifest.find(|item| item.has_property("nav"));
+    let ncx = opf
+        .descendants()
+        .find(|node| is(*node, OPF_NS, "spine"))
+        .and_then(|spine| spine.attribute("toc"))
+        .and_then(|id| manifest.get(id))
+        .or_else(|| manifest.find(|item| item.media_type == "application/x-dtbncx+xml"));
+
+    let mut toc = HashMap::new();
+    for (item, is_nav) in [(nav, true), (ncx, false)] {
+        let Some(item) = item else {
+            continue;
+        };
+        let Ok(Some(xml)) = package.read_string(&item.href) else {
+            continue;
+        };
+        let Ok(doc) = Document::parse(&xml) else {
+            continue;
+        };
+        let base = directory_of(&item.href);
+        let entries: Vec<(&str, String)> = if is_nav {
+            doc.descendants()
+                .filter(|node| node.tag_name().name() == "a")
+                .filter_map(|a| Some((a.attribute("href")?, text_content(a))))
+                .collect()
+        } else {
+            doc.descendants()
+                .filter(|node| node.tag_name().name() == "navPoint")
+                .filter_map(|point| {
+                    let label = point
+                        .children()
+                        .find(|n| n.tag_name().name() == "navLabel")
+                        .map(text_content)?;
+                    let src = point
+                        .children()
+                        .find(|n| n.tag_name().name() == "content")?
+                        .attribute("src")?;
+                    Some((src, label))
+                })
+                .collect()
+        };
+        for (href, label) in entries {
+            let path = resolve_path(base, href.split('#').next().unwrap_or_default());
+            if !label.is_empty() {
+                // The first entry pointing at a file is the chapter itself,
+                // later ones are sections inside it
+                toc.entry(path).or_insert(label);
+            }
+        }
+        if !toc.is_empty() {
+            break;
+        }
+    }
+    toc
+}
+
+fn text_content(node: Node) -> String {
+    node.descendants()
+        .filter(Node::is_text)
+        .filter_map(|n| n.text())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is(node: Node, namespace: &str, name: &str) -> bool {
+    node.is_element()
+        && node.tag_name().name() == name
+        && node.tag_name().namespace() == Some(namespace)
+}
+
+fn parse_xml<'a>(xml: &'a str, part: &str) -> Result<Document<'a>, EpubError> {
+    Document::parse(xml).map_err(|source| EpubError::Xml {
+        part: part.to_string(),
+        source,
+    })
+}
+
+pub(crate) fn directory_of(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(directory, _)| directory)
+}
+
+/// Resolves an href relative to the directory of the referencing document.
+/// Hrefs are URLs, so they are percent-decoded to get the zip entry name.
+pub(crate) fn resolve_path(directory: &str, href: &str) -> String {
+    resolve_part_path(directory, &percent_decode(href))
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            if let Some(byte) = input
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+
+    fn build_epub(parts: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file(
+                "mimetype",
+                SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored),
+            )
+            .unwrap();
+        writer.write_all(b"application/epub+zip").unwrap();
+        writer
+            .start_file("META-INF/container.xml", SimpleFileOptions::default())
+            .unwrap();
+        writer
+            .write_all(br#"<container xmlns="urn:oasis:names:tc:opendocument:xmlns:container"><rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles></container>"#)
+            .unwrap();
+        for (name, data) in parts {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    const OPF: &[u8] = br#"<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+        <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+            <dc:title>Flatland</dc:title><dc:creator>Edwin A. Abbott</dc:creator><dc:language>en</dc:language>
+        </metadata>
+        <manifest>
+            <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+            <item id="c1" href="text/chapter%201.xhtml" media-type="application/xhtml+xml"/>
+            <item id="c2" href="text/chapter2.xhtml" media-type="application/xhtml+xml"/>
+            <item id="cover" href="images/cover.jpg" media-type="image/jpeg" properties="cover-image"/>
+        </manifest>
+        <spine><itemref idref="c2"/><itemref idref="c1"/></spine>
+    </package>"#;
+
+    #[test]
+    fn converts_chapters_in_spine_order() {
+        let epub = build_epub(&[
+            ("OEBPS/content.opf", OPF),
+            (
+                "OEBPS/nav.xhtml",
+                br#"<html xmlns="http://www.w3.org/1999/xhtml"><body><nav><ol><li><a href="text/chapter2.xhtml">Of the Nature of Flatland</a></li></ol></nav></body></html>"#,
+            ),
+            (
+                "OEBPS/text/chapter 1.xhtml",
+                br#"<html><head><title>ignored</title></head><body><p>Second in spine.</p></body></html>"#,
+            ),
+            (
+                "OEBPS/text/chapter2.xhtml",
+                br#"<html><head><style>p { color: red }</style></head><body><h3>Part I</h3><p>I call our world <em>Flatland</em>&nbsp;<img src="../images/cover.jpg" alt="cover"/></p></body></html>"#,
+            ),
+            ("OEBPS/images/cover.jpg", b"\xFF\xD8\xFF"),
+        ]);
+        let parsed = parse_epub(&epub).unwrap();
+
+        assert_eq!(parsed.metadata.title.as_deref(), Some("Flatland"));
+        assert_eq!(parsed.metadata.creators, ["Edwin A. Abbott"]);
+        assert_eq!(parsed.chapters.len(), 2);
+        assert_eq!(parsed.chapters[0].href, "OEBPS/text/chapter2.xhtml");
+        assert_eq!(
+            parsed.chapters[0].title.as_deref(),
+            Some("Of the Nature of Flatland")
+        );
+        assert!(parsed.chapters[0].markdown.starts_with("### Part I"));
+        assert!(parsed.chapters[0]
+            .markdown
+            .contains("![cover](OEBPS/images/cover.jpg)"));
+        assert!(!parsed.chapters[0].markdown.contains("color"));
+        assert_eq!(parsed.chapters[1].href, "OEBPS/text/chapter 1.xhtml");
+        assert_eq!(parsed.chapters[1].markdown, "Second in spine.");
+        assert!(parsed.markdown.ends_with("Second in spine.\n"));
+
+        let cover = parsed.cover.unwrap();
+        assert_eq!(cover.name, "OEBPS/images/cover.jpg");
+        assert_eq!(cover.content_type, "image/jpeg");
+        assert_eq!(cover.data, b"\xFF\xD8\xFF");
+    }
+
+    #[test]
+    fn finds_epub2_covers_and_ncx_titles() {
+        let opf = br#"<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+            <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><meta name="cover" content="img"/></metadata>
+            <manifest>
+                <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+                <item id="c1" href="c1.html" media-type="application/xhtml+xml"/>
+                <item id="img" href="c.png" media-type="image/png"/>
+            </manifest>
+            <spine toc="ncx"><itemref idref="c1"/></spine>
+        </package>"#;
+        let ncx = br#"<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/"><navMap><navPoint id="p1"><navLabel><text>Opening</text></navLabel><content src="c1.html#start"/></navPoint></navMap></ncx>"#;
+        let epub = build_epub(&[
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/toc.ncx", ncx),
+            ("OEBPS/c1.html", b"<html><body><p>Hi</p></body></html>"),
+            ("OEBPS/c.png", b"\x89PNG"),
+        ]);
+        let parsed = parse_epub(&epub).unwrap();
+        assert_eq!(parsed.chapters[0].title.as_deref(), Some("Opening"));
+        assert_eq!(parsed.cover.unwrap().name, "OEBPS/c.png");
+    }
+
+    #[test]
+    fn picks_the_first_cover_in_manifest_order() {
+        let items: String = (0..20)
+            .map(|index| format!(r#"<item id="i{index}" href="c{index}.png" media-type="image/png" properties="cover-image"/>"#))
+            .collect();
+        let opf = format!(
+            r#"<package xmlns="http://www.idpf.org/2007/opf" version="3.0"><manifest>{items}</manifest><spine/></package>"#
+        );
+        let epub = build_epub(&[
+            ("OEBPS/content.opf", opf.as_bytes()),
+            ("OEBPS/c0.png", b"\x89PNG"),
+            ("OEBPS/c1.png", b"\x89PNG"),
+        ]);
+        assert_eq!(
+            parse_epub(&epub).unwrap().cover.unwrap().name,
+            "OEBPS/c0.png"
+        );
+    }
+
+    #[test]
+    fn rejects_archives_without_container() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file("a.txt", SimpleFileOptions::default())
+            .unwrap();
+        let archive = writer.finish().unwrap().into_inner();
+        assert!(matches!(
+            parse_epub(&archive),
+            Err(EpubError::MissingPart(_))
+        ));
+    }
+
+    #[test]
+    fn resolves_hrefs() {
+        assert_eq!(
+            resolve_path("OEBPS/text", "../img/a%20b.png"),
+            "OEBPS/img/a b.png"
+        );
+        assert_eq!(resolve_path("", "c1.html"), "c1.html");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
@@ -0,0 +1,51 @@
+//! C ABI for the API. Results are JSON strings owned by Rust and must be
+//! handed back to [`feed_free_string`].
+
+use std::ffi::{c_char, CStr};
+
+use ffi_support::{catch_panic, into_c_string};
+use serde_json::json;
+
+use crate::parse_feed;
+
+/// Parses the feed in `data[..len]` and returns the serialized
+/// [`crate::Feed`], or `{"error"}`. `base_url` is optional.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes and `base_url` must be null or a
+/// valid NUL-terminated string. The returned pointer must be released with
+/// [`feed_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn feed_parse(
+    data: *const u8,
+    len: usize,
+    base_url: *const c_char,
+) -> *mut c_char {
+    catch_panic(|| {
+        let bytes = if data.is_null() {
+            &[][..]
+        } else {
+            std::slice::from_raw_parts(data, len)
+        };
+        let base_url = if base_url.is_null() {
+            None
+        } else {
+            CStr::from_ptr(base_url).to_str().ok()
+        };
+        let result = match parse_feed(bytes, base_url) {
+            Ok(feed) => serde_json::to_string(&feed),
+            Err(err) => serde_json::to_string(&json!({ "error": err.to_string() })),
+        };
+        into_c_string(result.unwrap_or_else(|err| json!({ "error": err.to_string() }).to_string()))
+    })
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+/// `ptr` must come from a function of this library and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn feed_free_string(ptr: *mut c_char) {
+    ffi_support::free_string(ptr);
+}
@@ -0,0 +1,190 @@
+//! Normalizes RSS 0.9x/1.0/2.0, Atom and JSON Feed documents into a flat item
+//! list. Feed monitoring diffs these items between runs, and map/crawl use
+//! their URLs as an additional discovery source.
+
+use feed_rs::model::{Entry, FeedType, Link, Text};
+use feed_rs::parser::{self, ParseFeedError};
+use serde::Serialize;
+
+mod ffi;
+#[cfg(feature = "napi")]
+pub mod node;
+
+pub use ffi::{feed_free_string, feed_parse};
+
+#[derive(Debug, thiserror::Error)]
+pub enum FeedError {
+    #[error("not a valid feed: {0}")]
+    Parse(#[from] ParseFeedError),
+}
+
+#[derive(Debug, Serialize)]
+pub struct Feed {
+    /// `rss`, `atom` or `json`.
+    pub format: &'static str,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// The site the feed belongs to, not the feed itself.
+    pub url: Option<String>,
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Item {
+    /// Stable identifier (guid/id); derived from the link and title when the
+    /// feed doesn't provide one.
+    pub id: String,
+    pub title: Option<String>,
+    pub url: Option<String>,
+    /// RFC 3339, falls back to the update time for feeds that only carry one.
+    pub published: Option<String>,
+    pub updated: Option<String>,
+    pub summary: Option<String>,
+    pub content: Option<String>,
+    pub authors: Vec<String>,
+    pub categories: Vec<String>,
+}
+
+/// Parses a feed. `base_url` (usually the feed URL) resolves relative links
+/// in XML feeds.
+pub fn parse_feed(data: &[u8], base_url: Option<&str>) -> Result<Feed, FeedError> {
+    let feed = parser::Builder::new()
+        .base_uri(base_url)
+        .build()
+        .parse(data)?;
+    Ok(Feed {
+        format: match feed.feed_type {
+            FeedType::Atom => "atom",
+            FeedType::JSON => "json",
+            FeedType::RSS0 | FeedType::RSS1 | FeedType::RSS2 => "rss",
+        },
+        title: text(feed.title),
+        description: text(feed.description),
+        url: page_link(&feed.links),
+        items: feed.entries.into_iter().map(item).collect(),
+    })
+}
+
+fn item(entry: Entry) -> Item {
+    // RSS items with only a permalink guid have no <link>.
+    let url =
+        page_link(&entry.links).or_else(|| entry.id.starts_with("http").then(|| entry.id.clone()));
+    Item {
+        id: entry.id,
+        title: text(entry.title),
+        url,
+        published: entry
+            .published
+            .or(entry.updated)
+            .map(|date| date.to_rfc3339()),
+        updated: entry.updated.map(|date| date.to_rfc3339()),
+        summary: text(entry.summary),
+        content: entry.content.and_then(|content| content.body),
+        authors: entry
+            .authors
+            .into_iter()
+            .map(|person| person.name)
+            .filter(|name| !name.is_empty())
+            .collect(),
+        categories: entry
+            .categories
+            .into_iter()
+            .map(|category| category.label.unwrap_or(category.term))
+            .collect(),
+    }
+}
+
+/// The `alternate` link (or the unlabelled one RSS uses), else the first.
+fn page_link(links: &[Link]) -> Option<String> {
+    links
+        .iter()
+        .find(|link| matches!(link.rel.as_deref(), None | Some("alternate")))
+        .or_else(|| links.first())
+        .map(|link| link.href.clone())
+}
+
+fn text(text: Option<Text>) -> Option<String> {
+    text.map(|text| text.content.trim().to_string())
+        .filter(|content| !content.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rss() {
+        let rss = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel>
+  <title>Blog</title><link>https://example.com/</link><description>Posts</description>
+  <item>
+    <title>First</title><link>https://example.com/first</link>
+    <description>Short &lt;b&gt;summary&lt;/b&gt;</description>
+    <pubDate>Tue, 02 Jul 2024 10:00:00 GMT</pubDate>
+    <category>news</category>
+  </item>
+  <item><guid>https://example.com/second</guid><title>Second</title></item>
+</channel></rss>"#;
+        let feed = parse_feed(rss.as_bytes(), None).unwrap();
+        assert_eq!(feed.format, "rss");
+        assert_eq!(feed.title.as_deref(), Some("Blog"));
+        assert_eq!(feed.url.as_deref(), Some("https://example.com/"));
+        assert_eq!(feed.items.len(), 2);
+        let first = &feed.items[0];
+        assert_eq!(first.url.as_deref(), Some("https://example.com/first"));
+        assert_eq!(first.summary.as_deref(), Some("Short <b>summary</b>"));
+        assert_eq!(
+            first.published.as_deref(),
+            Some("2024-07-02T10:00:00+00:00")
+        );
+        assert_eq!(first.categories, ["news"]);
+        assert_eq!(
+            feed.items[1].url.as_deref(),
+            Some("https://example.com/second")
+        );
+    }
+
+    #[test]
+    fn parses_atom_with_relative_links() {
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Changelog</title><id>urn:feed</id><updated>2024-07-02T10:00:00Z</updated>
+  <entry>
+    <id>urn:entry:1</id><title>Release</title><updated>2024-07-01T08:00:00Z</updated>
+    <link rel="alternate" href="/changelog/1"/>
+    <author><name>Ada</name></author>
+    <content type="html">&lt;p&gt;Notes&lt;/p&gt;</content>
+  </entry>
+</feed>"#;
+        let feed = parse_feed(atom.as_bytes(), Some("https://example.com/feed.xml")).unwrap();
+        assert_eq!(feed.format, "atom");
+        let entry = &feed.items[0];
+        assert_eq!(entry.id, "urn:entry:1");
+        assert_eq!(
+            entry.url.as_deref(),
+            Some("https://example.com/changelog/1")
+        );
+        assert_eq!(
+            entry.published.as_deref(),
+            Some("2024-07-01T08:00:00+00:00")
+        );
+        assert_eq!(entry.authors, ["Ada"]);
+        assert!(entry.content.as_deref().unwrap().contains("Notes"));
+    }
+
+    #[test]
+    fn parses_json_feed() {
+        let json = r#"{"version":"https://jsonfeed.org/version/1.1","title":"J",
+            "items":[{"id":"1","url":"https://example.com/1","content_text":"Hello",
+            "date_published":"2024-07-02T10:00:00Z"}]}"#;
+        let feed = parse_feed(json.as_bytes(), None).unwrap();
+        assert_eq!(feed.format, "json");
+        assert_eq!(feed.items[0].url.as_deref(), Some("https://example.com/1"));
+        assert_eq!(feed.items[0].content.as_deref(), Some("Hello"));
+    }
+
+    #[test]
+    fn rejects_non_feeds() {
+        assert!(parse_feed(b"<html><body>hi</body></html>", None).is_err());
+        assert!(parse_feed(b"plain text", None).is_err());
+    }
+}
@@ -0,0 +1,15 @@
+//! Node-API bindings, built with `--features napi`.
+
+use napi::bindgen_prelude::Buffer;
+use napi_derive::napi;
+
+use crate::parse_feed;
+
+/// Returns the normalized feed as the same JSON string as the C ABI, but
+/// throws on failure instead of returning `{"error"}`.
+#[napi(js_name = "parseFeed")]
+pub fn feed_parse(data: Buffer, base_url: Option<String>) -> napi::Result<String> {
+    let feed = parse_feed(&data, base_url.as_deref())
+        .map_err(|err| napi::Error::from_reason(err.to_string()))?;
+    serde_json::to_string(&feed).map_err(|err| napi::Error::from_reason(err.to_string()))
+}
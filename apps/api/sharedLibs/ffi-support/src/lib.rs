@@ -0,0 +1,68 @@
+//! Plumbing shared by the C ABIs of the libraries in this workspace. Results
+//! cross the boundary as JSON strings owned by Rust, which every library
+//! releases through its own `<prefix>_free_string` export.
+
+use std::ffi::{c_char, CString};
+use std::fmt::Display;
+use std::panic::{self, AssertUnwindSafe};
+
+use serde_json::json;
+
+/// Hands `value` over to the caller as a NUL terminated string.
+pub fn into_c_string(value: String) -> *mut c_char {
+    // JSON escapes control characters, so the only NUL can come from a bug
+    CString::new(value)
+        .unwrap_or_else(|_| CString::new(r#"{"error":"output contained a NUL byte"}"#).unwrap())
+        .into_raw()
+}
+
+/// Returns `{"error": message}`.
+pub fn error_json(message: impl Display) -> *mut c_char {
+    into_c_string(json!({ "error": message.to_string() }).to_string())
+}
+
+/// Runs the body of an `extern "C"` function. Unwinding out of one aborts the
+/// host process, so a panic is reported as an error payload instead.
+pub fn catch_panic(f: impl FnOnce() -> *mut c_char) -> *mut c_char {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| error_json("internal error"))
+}
+
+/// Releases a string returned by [`into_c_string`]. Null is ignored.
+///
+/// # Safety
+/// `ptr` must come from [`into_c_string`] and must not be used afterwards.
+pub unsafe fn free_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        drop(CString::from_raw(ptr));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn reports_panics_as_json() {
+        let ptr = catch_panic(|| panic!("boom"));
+        unsafe {
+            assert_eq!(
+                CStr::from_ptr(ptr).to_str().unwrap(),
+                r#"{"error":"internal error"}"#
+            );
+            free_string(ptr);
+        }
+    }
+
+    #[test]
+    fn replaces_output_with_a_nul_byte() {
+        let ptr = into_c_string("a\0b".to_string());
+        unsafe {
+            assert_eq!(
+                CStr::from_ptr(ptr).to_str().unwrap(),
+                r#"{"error":"output contained a NUL byte"}"#
+            );
+            free_string(ptr);
+        }
+    }
+}
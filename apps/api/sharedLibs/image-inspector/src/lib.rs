@@ -0,0 +1,290 @@
+//! Reads format, dimensions and EXIF orientation from image bytes without
+//! decoding pixels, so the scraper can annotate image URLs and drop tracking
+//! pixels cheaply. Only the header is parsed; SVG dimensions come from the
+//! root element's `width`/`height` or `viewBox`.
+
+use std::io::Cursor;
+
+use imagesize::{Compression, ImageError, ImageType};
+use serde::Serialize;
+
+mod ffi;
+#[cfg(feature = "napi")]
+pub mod node;
+
+pub use ffi::{image_free_string, image_inspect};
+
+/// Images this small are beacons, not content.
+const TRACKING_PIXEL_MAX_SIDE: u64 = 2;
+
+#[derive(Debug, thiserror::Error)]
+pub enum InspectError {
+    #[error("unsupported or unrecognized image format")]
+    Unsupported,
+    #[error("corrupted image header")]
+    Corrupted,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageInfo {
+    pub format: &'static str,
+    pub width: u64,
+    pub height: u64,
+    /// EXIF orientation (1-8) when present.
+    pub orientation: Option<u16>,
+    /// Dimensions as displayed, i.e. swapped for rotated orientations.
+    pub display_width: u64,
+    pub display_height: u64,
+    pub byte_size: usize,
+    pub tracking_pixel: bool,
+}
+
+pub fn inspect(data: &[u8]) -> Result<ImageInfo, InspectError> {
+    let (format, width, height) = match svg_size(data) {
+        Some((width, height)) => ("svg", width, height),
+        None => {
+            let kind = imagesize::image_type(data).map_err(map_error)?;
+            let format = format_name(kind).ok_or(InspectError::Unsupported)?;
+            let size = imagesize::blob_size(data).map_err(map_error)?;
+            (format, size.width as u64, size.height as u64)
+        }
+    };
+    let orientation = if format == "svg" {
+        None
+    } else {
+        exif_orientation(data)
+    };
+    let rotated = matches!(orientation, Some(5..=8));
+    let (display_width, display_height) = if rotated {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    Ok(ImageInfo {
+        format,
+        width,
+        height,
+        orientation,
+        display_width,
+        display_height,
+        byte_size: data.len(),
+        tracking_pixel: width <= TRACKING_PIXEL_MAX_SIDE && height <= TRACKING_PIXEL_MAX_SIDE,
+    })
+}
+
+fn map_error(err: ImageError) -> InspectError {
+    match err {
+        ImageError::NotSupported => InspectError::Unsupported,
+        ImageError::CorruptedImage | ImageError::IoError(_) => InspectError::Corrupted,
+    }
+}
+
+/// Formats outside the enabled feature set are reported as unsupported.
+fn format_name(kind: ImageType) -> Option<&'static str> {
+    let name = match kind {
+        ImageType::Bmp => "bmp",
+        ImageType::Gif => "gif",
+        ImageType::Heif(Compression::Av1) => "avif",
+        ImageType::Heif(_) => "heif",
+        ImageType::Ico => "ico",
+        ImageType::Jpeg => "jpeg",
+        ImageType::Jxl => "jxl",
+        ImageType::Png => "png",
+        ImageType::Tiff => "tiff",
+        ImageType::Webp => "webp",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn exif_orientation(data: &[u8]) -> Option<u16> {
+    let exif = exif::Reader::new()
+        .read_from_container(&mut Cursor::new(data))
+        .ok()?;
+    let field = exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?;
+    let orientation = field.value.get_uint(0)?;
+    (1..=8).contains(&orientation).then_some(orientation as u16)
+}
+
+/// Size of an SVG from its root element, `None` if `data` isn't SVG. Sizes
+/// in units other than px (or unitless) fall back to the viewBox.
+fn svg_size(data: &[u8]) -> Option<(u64, u64)> {
+    let head = String::from_utf8_lossy(&data[..data.len().min(4096)]);
+    let start = head.find("<svg")?;
+    if !is_svg_prolog(head[..start].trim_start_matches('\u{feff}')) {
+        return None;
+    }
+    let tag = &head[start..start + head[start..].find('>')?];
+
+    let pixels = |name: &str| -> Option<u64> {
+        let value = attribute(tag, name)?;
+        let number = value.strip_suffix("px").unwrap_or(value).trim();
+        number
+            .parse::<f64>()
+            .ok()
+            .map(|pixels| pixels.round() as u64)
+    };
+    if let (Some(width), Some(height)) = (pixels("width"), pixels("height")) {
+        return Some((width, height));
+    }
+    let view_box: Vec<f64> = attribute(tag, "viewBox")?
+        .split([' ', ','])
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| part.parse().ok())
+        .collect();
+    match view_box[..] {
+        [_, _, width, height] => Some((width.round() as u64, height.round() as u64)),
+        _ => None,
+    }
+}
+
+/// Whether everything before the root `<svg` is an XML declaration, comments
+/// or an SVG doctype, so an HTML page with inline SVG isn't taken for one.
+fn is_svg_prolog(mut prolog: &str) -> bool {
+    loop {
+        prolog = prolog.trim_start();
+        if prolog.is_empty() {
+            return true;
+        }
+        let end = if prolog.starts_with("<?xml") {
+            prolog.find("?>").map(|end| end + 2)
+        } else if prolog.starts_with("<!--") {
+            prolog.find("-->").map(|end| end + 3)
+        } else if prolog.starts_with("<!DOCTYPE svg") {
+            // Skip an internal subset, whose declarations contain `>` too.
+            let subset_end = match (prolog.find('['), prolog.find('>')) {
+                (Some(open), Some(close)) if open < close => prolog.find(']').unwrap_or(open),
+                _ => 0,
+            };
+            prolog[subset_end..]
+                .find('>')
+                .map(|end| subset_end + end + 1)
+        } else {
+            None
+        };
+        match end {
+            Some(end) => prolog = &prolog[end..],
+            None => return false,
+        }
+    }
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(index) = rest.find(name) {
+        let preceded_by_space = rest[..index].ends_with(char::is_whitespace);
+        let after = rest[index + name.len()..].trim_start();
+        if let Some(value) = after.strip_prefix('=').filter(|_| preceded_by_space) {
+            let value = value.trim_start();
+            let quote = value.chars().next()?;
+            if quote == '"' || quote == '\'' {
+                let value = &value[1..];
+                return Some(&value[..value.find(quote)?]);
+            }
+        }
+        rest = &rest[index + name.len()..];
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        data.extend(width.to_be_bytes());
+        data.extend(height.to_be_bytes());
+        data.extend([8, 6, 0, 0, 0, 0, 0, 0, 0]);
+        data
+    }
+
+    /// JPEG with an APP1 EXIF segment carrying `orientation` and a SOF0.
+    fn jpeg(width: u16, height: u16, orientation: u16) -> Vec<u8> {
+        let mut tiff =
+            b"MM\x00\x2a\x00\x00\x00\x08\x00\x01\x01\x12\x00\x03\x00\x00\x00\x01".to_vec();
+        tiff.extend(orientation.to_be_bytes());
+        tiff.extend([0, 0, 0, 0, 0, 0]);
+        let mut data = vec![0xff, 0xd8, 0xff, 0xe1];
+        data.extend(((tiff.len() + 8) as u16).to_be_bytes());
+        data.extend(b"Exif\x00\x00");
+        data.extend(tiff);
+        data.extend([0xff, 0xc0, 0x00, 0x11, 0x08]);
+        data.extend(height.to_be_bytes());
+        data.extend(width.to_be_bytes());
+        data.extend([3, 1, 0x22, 0, 2, 0x11, 1, 3, 0x11, 1]);
+        data.extend([0xff, 0xd9]);
+        data
+    }
+
+    #[test]
+    fn reads_png_header() {
+        let info = inspect(&png(640, 480)).unwrap();
+        assert_eq!((info.format, info.width, info.height), ("png", 640, 480));
+        assert_eq!(info.orientation, None);
+        assert!(!info.tracking_pixel);
+    }
+
+    #[test]
+    fn flags_tracking_pixels() {
+        let gif = b"GIF89a\x01\x00\x01\x00\x80\x00\x00\xff\xff\xff\x00\x00\x00!\xf9\x04\x01\x00\x00\x00\x00,\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02D\x01\x00;";
+        let info = inspect(gif).unwrap();
+        assert_eq!(info.format, "gif");
+        assert!(info.tracking_pixel);
+        assert_eq!(info.byte_size, gif.len());
+    }
+
+    #[test]
+    fn applies_exif_orientation() {
+        let info = inspect(&jpeg(400, 300, 6)).unwrap();
+        assert_eq!((info.format, info.width, info.height), ("jpeg", 400, 300));
+        assert_eq!(info.orientation, Some(6));
+        assert_eq!((info.display_width, info.display_height), (300, 400));
+
+        let upright = inspect(&jpeg(400, 300, 1)).unwrap();
+        assert_eq!((upright.display_width, upright.display_height), (400, 300));
+    }
+
+    #[test]
+    fn reads_svg_root_size() {
+        let svg = br#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg" width="24px" height='16'></svg>"#;
+        let info = inspect(svg).unwrap();
+        assert_eq!((info.format, info.width, info.height), ("svg", 24, 16));
+
+        let view_box = br#"<svg stroke-width="2" viewBox="0 0 1 1"/>"#;
+        let info = inspect(view_box).unwrap();
+        assert_eq!((info.width, info.height), (1, 1));
+        assert!(info.tracking_pixel);
+    }
+
+    #[test]
+    fn rejects_unknown_data() {
+        assert!(matches!(
+            inspect(b"just some plain text, not an image"),
+            Err(InspectError::Unsupported)
+        ));
+        assert!(inspect(b"<html><svg width=\"1\"/>").is_err());
+    }
+
+    #[test]
+    fn accepts_only_svg_prologs() {
+        let svg = br#"<?xml version="1.0"?>
+<!-- icon -->
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd" [
+  <!ENTITY ns "http://www.w3.org/2000/svg">
+]>
+<svg width="32" height="32"/>"#;
+        assert_eq!(inspect(svg).unwrap().width, 32);
+
+        let html = br#"<!DOCTYPE html><html><body><svg width="1" height="1"/></body></html>"#;
+        assert!(inspect(html).is_err());
+        assert!(inspect(b"<!-- unterminated <svg width=\"1\" height=\"1\"/>").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_view_boxes() {
+        let svg = br#"<svg viewBox="0 0 wide tall"/>"#;
+        assert!(inspect(svg).is_err());
+    }
+}
@@ -0,0 +1,14 @@
+//! Node-API bindings, built with `--features napi`.
+
+use napi::bindgen_prelude::Buffer;
+use napi_derive::napi;
+
+use crate::inspect;
+
+/// Returns the image info as the same JSON string as the C ABI, but throws on
+/// failure instead of returning `{"error"}`.
+#[napi(js_name = "inspectImage")]
+pub fn image_inspect(data: Buffer) -> napi::Result<String> {
+    let info = inspect(&data).map_err(|err| napi::Error::from_reason(err.to_string()))?;
+    serde_json::to_string(&info).map_err(|err| napi::Error::from_reason(err.to_string()))
+}
@@ -0,0 +1,46 @@
+//! C ABI for the API. Results are JSON strings owned by Rust and must be
+//! handed back to [`llmstxt_free_string`].
+
+use std::ffi::{c_char, CStr};
+
+use ffi_support::{catch_panic, into_c_string};
+use serde_json::json;
+
+use crate::{build, Site};
+
+/// Builds both files from the NUL-terminated [`crate::Site`] JSON and
+/// returns `{"llmsTxt", "llmsFullTxt"}`, or `{"error"}`.
+///
+/// # Safety
+/// `input` must be a valid NUL-terminated string. The returned pointer must
+/// be released with [`llmstxt_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn llmstxt_build(input: *const c_char) -> *mut c_char {
+    catch_panic(|| {
+        let site = if input.is_null() {
+            Err("input is null".to_string())
+        } else {
+            CStr::from_ptr(input)
+                .to_str()
+                .map_err(|err| err.to_string())
+                .and_then(|input| {
+                    serde_json::from_str::<Site>(input).map_err(|err| err.to_string())
+                })
+        };
+        let value = match site {
+            Ok(site) => json!(build(&site)),
+            Err(err) => json!({ "error": err }),
+        };
+        into_c_string(value.to_string())
+    })
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+/// `ptr` must come from a function of this library and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn llmstxt_free_string(ptr: *mut c_char) {
+    ffi_support::free_string(ptr);
+}
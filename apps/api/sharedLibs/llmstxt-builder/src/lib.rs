@@ -0,0 +1,390 @@
+//! Assembles `llms.txt` and `llms-full.txt` from per-page titles, summaries
+//! and markdown. Pages are grouped into sections by their first path segment
+//! and every ordering decision depends only on the page data, so any API
+//! node produces byte-identical files for the same crawl regardless of the
+//! order pages finished in.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use text_segmenter::truncate_sentences;
+use url::Url;
+
+mod ffi;
+#[cfg(feature = "napi")]
+pub mod node;
+
+pub use ffi::{llmstxt_build, llmstxt_free_string};
+
+/// Heading for pages that live at the root of the site.
+const ROOT_SECTION: &str = "Pages";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Site {
+    /// Defaults to the host of the first page.
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub pages: Vec<Page>,
+    #[serde(default)]
+    pub options: Options,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Page {
+    pub url: String,
+    pub title: Option<String>,
+    /// One-line summary for `llms.txt`.
+    pub description: Option<String>,
+    /// Full content for `llms-full.txt`.
+    pub markdown: Option<String>,
+}
+
+/// Length budgets in characters; 0 means unlimited.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Options {
+    pub max_description_chars: usize,
+    pub max_page_chars: usize,
+    pub max_full_chars: usize,
+    pub max_pages: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            max_description_chars: 160,
+            max_page_chars: 0,
+            max_full_chars: 0,
+            max_pages: 0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Output {
+    pub llms_txt: String,
+    pub llms_full_txt: String,
+}
+
+struct Entry<'a> {
+    url: Url,
+    page: &'a Page,
+    segments: Vec<String>,
+}
+
+pub fn build(site: &Site) -> Output {
+    let options = &site.options;
+    let entries = entries(&site.pages);
+    let mut sections = group(entries);
+    if options.max_pages > 0 {
+        let mut remaining = options.max_pages;
+        for entries in sections.values_mut() {
+            entries.truncate(remaining);
+            remaining -= entries.len();
+        }
+        sections.retain(|_, entries| !entries.is_empty());
+    }
+
+    let title = site
+        .title
+        .as_deref()
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .map(str::to_string)
+        .or_else(|| {
+            let first = sections.values().flatten().next()?;
+            first.url.host_str().map(str::to_string)
+        })
+        .unwrap_or_else(|| "Website".to_string());
+    let mut header = format!("# {title}\n");
+    if let Some(description) = site.description.as_deref().map(collapse_whitespace) {
+        if !description.is_empty() {
+            header.push_str(&format!("\n> {description}\n"));
+        }
+    }
+
+    let mut llms_txt = header.clone();
+    for (key, entries) in &sections {
+        let heading = if key.is_empty() {
+            ROOT_SECTION.to_string()
+        } else {
+            prettify(key)
+        };
+        llms_txt.push_str(&format!("\n## {heading}\n\n"));
+        for entry in entries {
+            llms_txt.push_str(&format!(
+                "- [{}]({})",
+                escape(&page_title(entry)),
+                entry.url
+            ));
+            let description = entry
+                .page
+                .description
+                .as_deref()
+                .map(collapse_whitespace)
+                .map(|description| budget(&description, options.max_description_chars))
+                .unwrap_or_default();
+            if !description.is_empty() {
+                llms_txt.push_str(&format!(": {description}"));
+            }
+            llms_txt.push('\n');
+        }
+    }
+
+    let mut full_chars = header.chars().count();
+    let mut llms_full_txt = header;
+    for entry in sections.values().flatten() {
+        let Some(markdown) = entry.page.markdown.as_deref().map(str::trim) else {
+            continue;
+        };
+        if markdown.is_empty() {
+            continue;
+        }
+        let content = budget(markdown, options.max_page_chars);
+        let block = format!(
+            "\n## {}\n\nSource: {}\n\n{content}\n",
+            page_title(entry),
+            entry.url
+        );
+        if options.max_full_chars > 0 {
+            full_chars += block.chars().count();
+            if full_chars > options.max_full_chars {
+                break;
+            }
+        }
+        llms_full_txt.push_str(&block);
+    }
+
+    Output {
+        llms_txt,
+        llms_full_txt,
+    }
+}
+
+/// Parses and deduplicates the pages (fragments ignored). Sorting before
+/// deduplicating makes the surviving copy independent of input order.
+fn entries(pages: &[Page]) -> Vec<Entry<'_>> {
+    let mut entries: Vec<Entry> = pages
+        .iter()
+        .filter_map(|page| {
+            let mut url = Url::parse(page.url.trim()).ok()?;
+            url.set_fragment(None);
+            let segments = url
+                .path_segments()
+                .map(|segments| {
+                    segments
+                        .filter(|segment| !segment.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some(Entry {
+                url,
+                page,
+                segments,
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        a.url
+            .as_str()
+            .cmp(b.url.as_str())
+            .then_with(|| b.page.title.cmp(&a.page.title))
+            .then_with(|| b.page.description.cmp(&a.page.description))
+    });
+    entries.dedup_by(|later, earlier| later.url == earlier.url);
+    entries
+}
+
+/// Sections keyed by first path segment, root pages under the empty key.
+/// A single-segment page joins the section of the same name when one exists
+/// (`/blog` next to `/blog/post`), and is a root page otherwise.
+fn group(entries: Vec<Entry<'_>>) -> BTreeMap<String, Vec<Entry<'_>>> {
+    let directories: HashSet<String> = entries
+        .iter()
+        .filter(|entry| entry.segments.len() > 1)
+        .map(|entry| entry.segments[0].clone())
+        .collect();
+    let mut sections: BTreeMap<String, Vec<Entry>> = BTreeMap::new();
+    for entry in entries {
+        let key = match entry.segments.first() {
+            Some(first) if directories.contains(first) => first.clone(),
+            _ => String::new(),
+        };
+        sections.entry(key).or_default().push(entry);
+    }
+    for entries in sections.values_mut() {
+        entries.sort_by(|a, b| {
+            a.segments
+                .len()
+                .cmp(&b.segments.len())
+                .then_with(|| a.url.as_str().cmp(b.url.as_str()))
+        });
+    }
+    sections
+}
+
+fn page_title(entry: &Entry) -> String {
+    entry
+        .page
+        .title
+        .as_deref()
+        .map(collapse_whitespace)
+        .filter(|title| !title.is_empty())
+        .or_else(|| entry.segments.last().map(|segment| prettify(segment)))
+        .or_else(|| entry.url.host_str().map(str::to_string))
+        .unwrap_or_else(|| entry.url.to_string())
+}
+
+/// `getting-started.html` → `Getting Started`.
+fn prettify(segment: &str) -> String {
+    let stem = segment.rsplit_once('.').map_or(segment, |(stem, _)| stem);
+    stem.split(['-', '_', ' '])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn escape(title: &str) -> String {
+    title.replace('[', "\\[").replace(']', "\\]")
+}
+
+fn budget(text: &str, max_chars: usize) -> String {
+    if max_chars == 0 || text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        truncate_sentences(text, max_chars, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(url: &str, title: &str, description: &str) -> Page {
+        Page {
+            url: url.to_string(),
+            title: Some(title.to_string()),
+            description: Some(description.to_string()),
+            markdown: Some(format!("# {title}\n\n{description}")),
+        }
+    }
+
+    fn site(pages: Vec<Page>) -> Site {
+        Site {
+            title: Some("Example".to_string()),
+            description: Some("An example   site.".to_string()),
+            pages,
+            options: Options::default(),
+        }
+    }
+
+    #[test]
+    fn groups_pages_by_path() {
+        let output = build(&site(vec![
+            page(
+                "https://example.com/docs/install",
+                "Install",
+                "How to install.",
+            ),
+            page("https://example.com/about", "About", "Who we are."),
+            page("https://example.com/docs", "Docs", "Documentation home."),
+            page("https://example.com/getting-started/quick", "Quick", ""),
+            page("https://example.com/", "Home", "Welcome."),
+        ]));
+        assert_eq!(
+            output.llms_txt,
+            "# Example\n\n> An example site.\n\
+             \n## Pages\n\n\
+             - [Home](https://example.com/): Welcome.\n\
+             - [About](https://example.com/about): Who we are.\n\
+             \n## Docs\n\n\
+             - [Docs](https://example.com/docs): Documentation home.\n\
+             - [Install](https://example.com/docs/install): How to install.\n\
+             \n## Getting Started\n\n\
+             - [Quick](https://example.com/getting-started/quick)\n"
+        );
+    }
+
+    #[test]
+    fn output_is_independent_of_input_order() {
+        let pages = vec![
+            page("https://example.com/b", "B", "Second."),
+            page("https://example.com/a#intro", "A", "First."),
+            page("https://example.com/a", "A (copy)", "Duplicate."),
+            page("https://example.com/c/d", "D", "Nested."),
+        ];
+        let mut reversed = pages.clone();
+        reversed.reverse();
+        let forward = build(&site(pages));
+        let backward = build(&site(reversed));
+        assert_eq!(forward.llms_txt, backward.llms_txt);
+        assert_eq!(forward.llms_full_txt, backward.llms_full_txt);
+        assert_eq!(
+            forward.llms_txt.matches("](https://example.com/a)").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn applies_budgets() {
+        let mut site = site(vec![
+            page(
+                "https://example.com/a",
+                "A",
+                "First sentence is short. Second sentence makes it too long for the budget.",
+            ),
+            page("https://example.com/b", "B", "Another page."),
+        ]);
+        site.options.max_description_chars = 30;
+        site.options.max_full_chars = 160;
+        let output = build(&site);
+        assert!(output
+            .llms_txt
+            .contains("- [A](https://example.com/a): First sentence is short.\n"));
+        assert!(output
+            .llms_full_txt
+            .contains("Source: https://example.com/a"));
+        assert!(!output
+            .llms_full_txt
+            .contains("Source: https://example.com/b"));
+        assert!(output.llms_full_txt.chars().count() <= 160);
+
+        site.options.max_pages = 1;
+        let output = build(&site);
+        assert!(!output.llms_txt.contains("example.com/b"));
+    }
+
+    #[test]
+    fn falls_back_for_missing_titles() {
+        let output = build(&Site {
+            title: None,
+            description: None,
+            pages: vec![Page {
+                url: "https://docs.example.com/api_reference.html".to_string(),
+                title: None,
+                description: None,
+                markdown: None,
+            }],
+            options: Options::default(),
+        });
+        assert_eq!(
+            output.llms_txt,
+            "# docs.example.com\n\n## Pages\n\n- [Api Reference](https://docs.example.com/api_reference.html)\n"
+        );
+        assert_eq!(output.llms_full_txt, "# docs.example.com\n");
+    }
+}
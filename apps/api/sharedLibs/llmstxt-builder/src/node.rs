@@ -0,0 +1,15 @@
+//! Node-API bindings, built with `--features napi`.
+
+use napi_derive::napi;
+
+use crate::{build, Site};
+
+/// Takes the site JSON and returns `{"llmsTxt", "llmsFullTxt"}` as a JSON
+/// string, like the C ABI, but throws on invalid input instead of returning
+/// `{"error"}`.
+#[napi(js_name = "buildLlmsTxt")]
+pub fn build_llms_txt(input: String) -> napi::Result<String> {
+    let site: Site =
+        serde_json::from_str(&input).map_err(|err| napi::Error::from_reason(err.to_string()))?;
+    serde_json::to_string(&build(&site)).map_err(|err| napi::Error::from_reason(err.to_string()))
+}
@@ -0,0 +1,488 @@
+//! Splits markdown into heading-aware chunks under a token budget for the
+//! extract and RAG pipelines. Chunks are contiguous slices of the input, so
+//! their offsets always point back at the exact source text; consecutive
+//! chunks of the same section overlap by up to `overlap_tokens`.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tiktoken_rs::CoreBPE;
+use xxhash_rust::xxh3::xxh3_64;
+
+mod ffi;
+#[cfg(feature = "napi")]
+pub mod node;
+
+pub use ffi::{markdown_chunk, markdown_chunker_free_string};
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    /// GPT-3.5/GPT-4.
+    #[default]
+    Cl100kBase,
+    /// GPT-4o and later.
+    O200kBase,
+}
+
+impl Encoding {
+    fn bpe(self) -> &'static CoreBPE {
+        match self {
+            Encoding::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+            Encoding::O200kBase => tiktoken_rs::o200k_base_singleton(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Options {
+    pub max_tokens: usize,
+    pub overlap_tokens: usize,
+    pub encoding: Encoding,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            max_tokens: 512,
+            overlap_tokens: 64,
+            encoding: Encoding::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Chunk {
+    /// Hash of the heading path and text, so re-chunking an unchanged
+    /// section yields the same id. Repeats within a document get an
+    /// occurrence counter mixed in.
+    pub id: String,
+    pub index: usize,
+    pub text: String,
+    /// Headings enclosing the chunk, outermost first.
+    pub headings: Vec<String>,
+    pub tokens: usize,
+    /// UTF-16 offsets, so `markdown.slice(start, end)` in JS is `text`.
+    pub start: usize,
+    pub end: usize,
+    pub byte_start: usize,
+    pub byte_end: usize,
+}
+
+/// A block that is never split across chunks unless it alone exceeds the
+/// budget: a heading line, a paragraph, a list, a fenced code block.
+struct Unit {
+    start: usize,
+    end: usize,
+    heading: bool,
+    path: usize,
+}
+
+struct Counter<'a> {
+    bpe: &'a CoreBPE,
+    source: &'a str,
+}
+
+impl Counter<'_> {
+    fn tokens(&self, start: usize, end: usize) -> usize {
+        self.bpe.encode_ordinary(&self.source[start..end]).len()
+    }
+
+    /// Byte offset at which each token of `source[start..end]` ends.
+    fn token_ends(&self, start: usize, end: usize) -> Vec<usize> {
+        let tokens = self.bpe.encode_ordinary(&self.source[start..end]);
+        let mut offset = start;
+        self.bpe
+            ._decode_native_and_split(tokens)
+            .map(|bytes| {
+                offset += bytes.len();
+                offset
+            })
+            .collect()
+    }
+}
+
+pub fn chunk_markdown(markdown: &str, options: &Options) -> Vec<Chunk> {
+    let max = options.max_tokens.max(1);
+    let counter = Counter {
+        bpe: options.encoding.bpe(),
+        source: markdown,
+    };
+    let (blocks, paths) = blocks(markdown);
+    let mut units = Vec::with_capacity(blocks.len());
+    for unit in blocks {
+        if counter.tokens(unit.start, unit.end) <= max {
+            units.push(unit);
+            continue;
+        }
+        for (start, end) in split_range(&counter, unit.start, unit.end, max) {
+            units.push(Unit {
+                start,
+                end,
+                heading: unit.heading,
+                path: unit.path,
+            });
+        }
+    }
+
+    let mut chunks = Vec::new();
+    let mut seen_ids: HashMap<u64, usize> = HashMap::new();
+    // Chunk starts and ends both only move forward
+    let (mut starts, mut ends) = (Utf16Offsets::new(markdown), Utf16Offsets::new(markdown));
+    let mut first = 0;
+    let mut first_new = 0;
+    while first < units.len() {
+        let mut last = first_new;
+        while let Some(next) = units.get(last + 1) {
+            let has_content = units[first..=last].iter().any(|unit| !unit.heading);
+            if (next.heading && has_content) || counter.tokens(units[first].start, next.end) > max {
+                break;
+            }
+            last += 1;
+        }
+
+        let (start, end) = (units[first].start, units[last].end);
+        let text = &markdown[start..end];
+        let headings = paths[units[first_new].path].clone();
+        chunks.push(Chunk {
+            id: chunk_id(&headings, text, &mut seen_ids),
+            index: chunks.len(),
+            text: text.to_string(),
+            headings,
+            tokens: counter.tokens(start, end),
+            start: starts.at(start),
+            end: ends.at(end),
+            byte_start: start,
+            byte_end: end,
+        });
+
+        // Start the next chunk a few units back, as long as it still leaves
+        // room for at least one new unit and stays within the section.
+        let next = last + 1;
+        let chunk_first = first;
+        first = next;
+        first_new = next;
+        if next < units.len() && !units[next].heading {
+            let mut overlap = 0;
+            let mut candidate = last;
+            while candidate > chunk_first {
+                overlap += counter.tokens(units[candidate].start, units[candidate].end);
+                if overlap > options.overlap_tokens
+                    || counter.tokens(units[candidate].start, units[next].end) > max
+                {
+                    break;
+                }
+                first = candidate;
+                candidate -= 1;
+            }
+        }
+    }
+    chunks
+}
+
+/// Converts byte offsets of `text` to UTF-16 offsets. Offsets are expected to
+/// grow, so each stretch of text is only counted once; going back restarts
+/// the count from the beginning.
+struct Utf16Offsets<'a> {
+    text: &'a str,
+    byte: usize,
+    utf16: usize,
+}
+
+impl<'a> Utf16Offsets<'a> {
+    fn new(text: &'a str) -> Self {
+        Utf16Offsets {
+            text,
+            byte: 0,
+            utf16: 0,
+        }
+    }
+
+    fn at(&mut self, byte: usize) -> usize {
+        if byte < self.byte {
+            (self.byte, self.utf16) = (0, 0);
+        }
+        self.utf16 += self.text[self.byte..byte].encode_utf16().count();
+        self.byte = byte;
+        self.utf16
+    }
+}
+
+fn chunk_id(headings: &[String], text: &str, seen: &mut HashMap<u64, usize>) -> String {
+    let key = format!("{}\0{text}", headings.join("\0"));
+    let hash = xxh3_64(key.as_bytes());
+    let occurrence = seen.entry(hash).or_default();
+    let id = if *occurrence == 0 {
+        hash
+    } else {
+        xxh3_64(format!("{key}\0{occurrence}").as_bytes())
+    };
+    *occurrence += 1;
+    format!("{id:016x}")
+}
+
+/// Splits the document into blocks and records the heading path in effect
+/// for each of them.
+fn blocks(markdown: &str) -> (Vec<Unit>, Vec<Vec<String>>) {
+    let mut units = Vec::new();
+    let mut paths = vec![Vec::new()];
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut block: Option<(usize, usize)> = None;
+    let mut fence: Option<&str> = None;
+    let mut offset = 0;
+
+    for line in markdown.split_inclusive('\n') {
+        let start = offset;
+        let end = offset + line.trim_end_matches(['\r', '\n']).len();
+        offset += line.len();
+        let trimmed = line.trim();
+
+        let flush = |block: &mut Option<(usize, usize)>, units: &mut Vec<Unit>, path| {
+            if let Some((start, end)) = block.take() {
+                units.push(Unit {
+                    start,
+                    end,
+                    heading: false,
+                    path,
+                });
+            }
+        };
+
+        if let Some(marker) = fence {
+            block = Some((block.map_or(start, |(start, _)| start), end));
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+        } else if let Some(marker) = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker))
+        {
+            fence = Some(marker);
+            block = Some((block.map_or(start, |(start, _)| start), end));
+        } else if trimmed.is_empty() {
+            flush(&mut block, &mut units, paths.len() - 1);
+        } else if let Some((level, text)) = heading(line) {
+            flush(&mut block, &mut units, paths.len() - 1);
+            while stack.last().is_some_and(|(open, _)| *open >= level) {
+                stack.pop();
+            }
+            stack.push((level, text.to_string()));
+            paths.push(stack.iter().map(|(_, text)| text.clone()).collect());
+            units.push(Unit {
+                start,
+                end,
+                heading: true,
+                path: paths.len() - 1,
+            });
+        } else {
+            block = Some((block.map_or(start, |(start, _)| start), end));
+        }
+    }
+    if let Some((start, end)) = block {
+        units.push(Unit {
+            start,
+            end,
+            heading: false,
+            path: paths.len() - 1,
+        });
+    }
+    (units, paths)
+}
+
+/// ATX heading level and text.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let level = rest.len() - rest.trim_start_matches('#').len();
+    let text = &rest[level..];
+    if !(1..=6).contains(&level) || !(text.trim().is_empty() || text.starts_with([' ', '\t'])) {
+        return None;
+    }
+    Some((level, text.trim().trim_end_matches('#').trim_end()))
+}
+
+/// Cuts an oversized block at the last line break that fits, else the last
+/// word break, else the last character boundary. The block is tokenized once
+/// and each cut is only searched for within the next `max` tokens, so long
+/// blocks split in linear time.
+fn split_range(counter: &Counter, start: usize, end: usize, max: usize) -> Vec<(usize, usize)> {
+    let source = counter.source;
+    let token_ends = counter.token_ends(start, end);
+    let mut pieces = Vec::new();
+    let mut cursor = start;
+    loop {
+        let rest = &source[cursor..end];
+        cursor += rest.len() - rest.trim_start().len();
+        if cursor >= end {
+            break;
+        }
+        // Tokens of the whole block only approximate those of the rest, so
+        // the window gets one token of slack and every cut is still counted
+        // on its own.
+        let first = token_ends.partition_point(|&token_end| token_end <= cursor);
+        let mut window_end = token_ends
+            .get(first + max)
+            .map_or(end, |&token_end| token_end);
+        if window_end == end && counter.tokens(cursor, end) <= max {
+            pieces.push((cursor, end));
+            break;
+        }
+        while !source.is_char_boundary(window_end) {
+            window_end -= 1;
+        }
+        let window = &source[cursor..window_end];
+        let candidates = |predicate: fn(char) -> bool| -> Vec<usize> {
+            window
+                .char_indices()
+                .filter(|(index, ch)| *index > 0 && predicate(*ch))
+                .map(|(index, _)| cursor + index)
+                .collect()
+        };
+        let cut = [
+            candidates(|ch| ch == '\n'),
+            candidates(char::is_whitespace),
+            candidates(|_| true),
+        ]
+        .into_iter()
+        .find_map(|ends| {
+            let fitting = ends.partition_point(|&end| counter.tokens(cursor, end) <= max);
+            fitting.checked_sub(1).map(|index| ends[index])
+        })
+        // A single character over budget: take it anyway to make progress.
+        .unwrap_or_else(|| cursor + rest.trim_start().chars().next().map_or(1, char::len_utf8));
+        let piece_end = cursor + source[cursor..cut].trim_end().len();
+        pieces.push((cursor, piece_end));
+        cursor = cut;
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(max_tokens: usize, overlap_tokens: usize) -> Options {
+        Options {
+            max_tokens,
+            overlap_tokens,
+            ..Options::default()
+        }
+    }
+
+    fn assert_slices(markdown: &str, chunks: &[Chunk]) {
+        for chunk in chunks {
+            assert_eq!(&markdown[chunk.byte_start..chunk.byte_end], chunk.text);
+        }
+    }
+
+    #[test]
+    fn keeps_small_documents_whole() {
+        let markdown = "Just a paragraph.\n\nAnd another one.\n";
+        let chunks = chunk_markdown(markdown, &Options::default());
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].text, "Just a paragraph.\n\nAnd another one.");
+        assert!(chunks[0].headings.is_empty());
+    }
+
+    #[test]
+    fn starts_a_chunk_at_each_heading() {
+        let markdown = "# Guide\n## Install\n\nRun it.\n\n## Usage\n\nCall it.\n\n# Other\n\nText";
+        let chunks = chunk_markdown(markdown, &Options::default());
+        let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "# Guide\n## Install\n\nRun it.",
+                "## Usage\n\nCall it.",
+                "# Other\n\nText"
+            ]
+        );
+        assert_eq!(chunks[0].headings, ["Guide"]);
+        assert_eq!(chunks[1].headings, ["Guide", "Usage"]);
+        assert_eq!(chunks[2].headings, ["Other"]);
+    }
+
+    #[test]
+    fn respects_budget_with_overlap() {
+        let markdown = (0..40)
+            .map(|index| format!("Paragraph number {index} talks about something."))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let chunks = chunk_markdown(&markdown, &options(40, 12));
+        assert!(chunks.len() > 5);
+        assert_slices(&markdown, &chunks);
+        for pair in chunks.windows(2) {
+            assert!(pair[0].tokens <= 40);
+            assert!(
+                pair[1].byte_start < pair[0].byte_end,
+                "chunks should overlap"
+            );
+            assert!(pair[1].byte_end > pair[0].byte_end, "chunks should advance");
+        }
+        assert!(chunks
+            .last()
+            .unwrap()
+            .text
+            .ends_with("number 39 talks about something."));
+
+        let without_overlap = chunk_markdown(&markdown, &options(40, 0));
+        for pair in without_overlap.windows(2) {
+            assert!(pair[1].byte_start > pair[0].byte_end);
+        }
+    }
+
+    #[test]
+    fn splits_oversized_blocks() {
+        let markdown = format!("{}\n{}", "word ".repeat(300).trim_end(), "x".repeat(500));
+        let chunks = chunk_markdown(&markdown, &options(50, 0));
+        assert_slices(&markdown, &chunks);
+        assert!(chunks.iter().all(|chunk| chunk.tokens <= 50));
+        let non_blank = |text: &str| text.chars().filter(|ch| !ch.is_whitespace()).count();
+        let covered: usize = chunks.iter().map(|chunk| non_blank(&chunk.text)).sum();
+        assert_eq!(covered, non_blank(&markdown));
+    }
+
+    #[test]
+    fn splits_long_blocks_in_linear_time() {
+        let markdown = "word ".repeat(10_000);
+        let chunks = chunk_markdown(&markdown, &options(100, 0));
+        assert_slices(&markdown, &chunks);
+        assert!(chunks.iter().all(|chunk| chunk.tokens <= 100));
+        // Cuts land close to the budget rather than wherever the window ends.
+        assert!(chunks.len() < 10_000 / 90);
+    }
+
+    #[test]
+    fn keeps_code_fences_together() {
+        let markdown = "Intro\n\n```\n# not a heading\n\nstill code\n```\n\nOutro";
+        let chunks = chunk_markdown(markdown, &Options::default());
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].headings.is_empty());
+    }
+
+    #[test]
+    fn ids_are_stable_and_unique() {
+        let markdown = "# A\n\nSame text\n\n# A\n\nSame text";
+        let first = chunk_markdown(markdown, &Options::default());
+        let second = chunk_markdown(markdown, &Options::default());
+        assert_eq!(first.len(), 2);
+        assert_ne!(first[0].id, first[1].id);
+        assert_eq!(
+            first.iter().map(|chunk| &chunk.id).collect::<Vec<_>>(),
+            second.iter().map(|chunk| &chunk.id).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn reports_utf16_offsets() {
+        let markdown = "# 😀 Emoji\n\nText";
+        let chunks = chunk_markdown(markdown, &Options::default());
+        assert_eq!((chunks[0].start, chunks[0].end), (0, 16));
+        assert_eq!(chunks[0].byte_end, markdown.len());
+    }
+}
@@ -0,0 +1,18 @@
+//! Node-API bindings, built with `--features napi`.
+
+use napi_derive::napi;
+
+use crate::{chunk_markdown, Options};
+
+/// Returns the chunk list as the same JSON array as the C ABI, but throws on
+/// invalid options instead of returning `{"error"}`.
+#[napi(js_name = "chunkMarkdown")]
+pub fn markdown_chunk(markdown: String, options: Option<String>) -> napi::Result<String> {
+    let options = match options {
+        Some(options) => serde_json::from_str::<Options>(&options)
+            .map_err(|err| napi::Error::from_reason(format!("invalid options: {err}")))?,
+        None => Options::default(),
+    };
+    serde_json::to_string(&chunk_markdown(&markdown, &options))
+        .map_err(|err| napi::Error::from_reason(err.to_string()))
+}
@@ -0,0 +1,54 @@
+//! C ABI for the API. Results are JSON strings owned by Rust and must be
+//! handed back to [`markdown_postprocessor_free_string`].
+
+use std::ffi::{c_char, CStr};
+
+use ffi_support::{catch_panic, error_json, into_c_string};
+use serde_json::json;
+
+use crate::{postprocess, Options};
+
+/// Cleans up the NUL-terminated UTF-8 `markdown` and returns
+/// `{"markdown"}`, or `{"error"}`. `options` is an optional JSON object
+/// overriding [`Options`] (`{"dedupeLinkRuns": false}`).
+///
+/// # Safety
+/// `markdown` must be a valid NUL-terminated string and `options` null or
+/// one. The returned pointer must be released with [`markdown_postprocessor_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn markdown_postprocess(
+    markdown: *const c_char,
+    options: *const c_char,
+) -> *mut c_char {
+    catch_panic(|| {
+        if markdown.is_null() {
+            return error_json("markdown is null".to_string());
+        }
+        let Ok(markdown) = CStr::from_ptr(markdown).to_str() else {
+            return error_json("markdown is not valid UTF-8".to_string());
+        };
+        let options = if options.is_null() {
+            Options::default()
+        } else {
+            match CStr::from_ptr(options)
+                .to_str()
+                .map_err(|err| err.to_string())
+                .and_then(|options| serde_json::from_str(options).map_err(|err| err.to_string()))
+            {
+                Ok(options) => options,
+                Err(err) => return error_json(format!("invalid options: {err}")),
+            }
+        };
+        into_c_string(json!({ "markdown": postprocess(markdown, &options) }).to_string())
+    })
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+/// `ptr` must come from a function of this library and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn markdown_postprocessor_free_string(ptr: *mut c_char) {
+    ffi_support::free_string(ptr);
+}
@@ -0,0 +1,386 @@
+//! Cleanup passes run on the markdown produced by the HTML transformer:
+//! empty headings, runs of blank lines, navigation link blocks repeated on
+//! every page section, and heading levels that skip or start below `#`.
+//! Every pass is optional so requests can opt out individually.
+
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+mod ffi;
+#[cfg(feature = "napi")]
+pub mod node;
+
+pub use ffi::{markdown_postprocess, markdown_postprocessor_free_string};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Options {
+    /// Drops headings without text, including anchor-only ones like
+    /// `## [](#section)`.
+    pub remove_empty_headings: bool,
+    /// Keeps at most one blank line between blocks.
+    pub collapse_blank_lines: bool,
+    /// Drops a block made only of links when the same block already appeared
+    /// earlier in the document.
+    pub dedupe_link_runs: bool,
+    /// Minimum number of links for a block to count as navigation.
+    pub min_link_run: usize,
+    /// Shifts headings so the top level is `#` and no level is skipped.
+    pub normalize_headings: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            remove_empty_headings: true,
+            collapse_blank_lines: true,
+            dedupe_link_runs: true,
+            min_link_run: 3,
+            normalize_headings: true,
+        }
+    }
+}
+
+struct Line {
+    text: String,
+    /// Inside (or delimiting) a fenced code block; never rewritten.
+    code: bool,
+}
+
+pub fn postprocess(markdown: &str, options: &Options) -> String {
+    let mut lines = classify(markdown);
+    // Empty headings go first so they don't count as a level when the rest
+    // are normalized.
+    if options.remove_empty_headings {
+        lines.retain(|line| {
+            line.code || !matches!(heading(&line.text), Some((_, text)) if is_blank_heading(text))
+        });
+    }
+    if options.normalize_headings {
+        normalize_headings(&mut lines);
+    }
+    if options.dedupe_link_runs {
+        lines = dedupe_link_runs(lines, options.min_link_run.max(1));
+    }
+    if options.collapse_blank_lines {
+        lines = collapse_blank_lines(lines);
+    }
+    let mut output = lines
+        .into_iter()
+        .map(|line| line.text)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if markdown.ends_with('\n') && !output.is_empty() {
+        output.push('\n');
+    }
+    output
+}
+
+fn classify(markdown: &str) -> Vec<Line> {
+    let mut fence: Option<&str> = None;
+    markdown
+        .lines()
+        .map(|text| {
+            let trimmed = text.trim_start();
+            let marker = ["```", "~~~"]
+                .into_iter()
+                .find(|marker| trimmed.starts_with(marker));
+            let code = match (fence, marker) {
+                (None, Some(marker)) => {
+                    fence = Some(marker);
+                    true
+                }
+                (Some(open), Some(marker)) if open == marker => {
+                    fence = None;
+                    true
+                }
+                (open, _) => open.is_some(),
+            };
+            Line {
+                text: text.to_string(),
+                code,
+            }
+        })
+        .collect()
+}
+
+/// ATX heading level and text, without the optional closing `#`s.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let level = rest.len() - rest.trim_start_matches('#').len();
+    let text = &rest[level..];
+    if !(1..=6).contains(&level) || !(text.is_empty() || text.starts_with([' ', '\t'])) {
+        return None;
+    }
+    let text = text.trim();
+    let without_closing = text.trim_end_matches('#');
+    let text = if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+        without_closing.trim_end()
+    } else {
+        text
+    };
+    Some((level, text))
+}
+
+fn is_blank_heading(text: &str) -> bool {
+    let mut rest = text.trim();
+    // Anchor links generated next to headings (`[](#id)`, `[#](#id)`).
+    while let Some(after) = rest
+        .strip_prefix("[](")
+        .or_else(|| rest.strip_prefix("[#]("))
+    {
+        match after.find(')') {
+            Some(end) => rest = after[end + 1..].trim(),
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+fn normalize_headings(lines: &mut [Line]) {
+    let Some(top) = lines
+        .iter()
+        .filter(|line| !line.code)
+        .filter_map(|line| heading(&line.text))
+        .map(|(level, _)| level)
+        .min()
+    else {
+        return;
+    };
+    let mut previous = 0;
+    for line in lines.iter_mut().filter(|line| !line.code) {
+        let Some((level, text)) = heading(&line.text) else {
+            continue;
+        };
+        let normalized = (level - (top - 1)).min(previous + 1);
+        previous = normalized;
+        if normalized != level {
+            line.text = if text.is_empty() {
+                "#".repeat(normalized)
+            } else {
+                format!("{} {text}", "#".repeat(normalized))
+            };
+        }
+    }
+}
+
+/// Number of links on a line made of nothing but links (optionally as a list
+/// item, separated by whitespace or `|`/`·`/`•`), `None` for any other line.
+fn link_only(line: &str) -> Option<usize> {
+    let mut rest = line.trim();
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(item) = rest.strip_prefix(marker) {
+            rest = item;
+        }
+    }
+    if let Some((number, item)) = rest.split_once(". ") {
+        if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) {
+            rest = item;
+        }
+    }
+
+    let mut links = 0;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || "|·•".contains(c));
+        if rest.is_empty() {
+            return (links > 0).then_some(links);
+        }
+        rest = skip_link(rest)?;
+        links += 1;
+    }
+}
+
+/// Skips one `[text](url)` (or image) at the start of `text`, allowing
+/// nested brackets for linked images.
+fn skip_link(text: &str) -> Option<&str> {
+    let text = text.strip_prefix('!').unwrap_or(text);
+    if !text.starts_with('[') {
+        return None;
+    }
+    let mut depth = 0;
+    let mut label_end = None;
+    for (index, ch) in text.char_indices() {
+        match ch {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    label_end = Some(index);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let after = text[label_end? + 1..].strip_prefix('(')?;
+    let mut depth = 1;
+    for (index, ch) in after.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&after[index + 1..]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn dedupe_link_runs(lines: Vec<Line>, min_links: usize) -> Vec<Line> {
+    let mut seen = HashSet::new();
+    let mut drop = vec![false; lines.len()];
+    let mut index = 0;
+    while index < lines.len() {
+        if lines[index].code || link_only(&lines[index].text).is_none() {
+            index += 1;
+            continue;
+        }
+        // A run is link-only lines, possibly separated by blank lines.
+        let start = index;
+        let mut end = index;
+        let mut links = 0;
+        let mut key = String::new();
+        let mut cursor = index;
+        while cursor < lines.len() && !lines[cursor].code {
+            let text = lines[cursor].text.trim();
+            if let Some(count) = link_only(text) {
+                links += count;
+                key.push_str(text);
+                key.push('\n');
+                end = cursor;
+            } else if !text.is_empty() {
+                break;
+            }
+            cursor += 1;
+        }
+        if links >= min_links && !seen.insert(key) {
+            drop[start..=end].fill(true);
+        }
+        index = end + 1;
+    }
+    lines
+        .into_iter()
+        .zip(drop)
+        .filter_map(|(line, drop)| (!drop).then_some(line))
+        .collect()
+}
+
+fn collapse_blank_lines(lines: Vec<Line>) -> Vec<Line> {
+    let mut output: Vec<Line> = Vec::with_capacity(lines.len());
+    for line in lines {
+        let blank = !line.code && line.text.trim().is_empty();
+        let previous_blank = output
+            .last()
+            .is_none_or(|last| !last.code && last.text.is_empty());
+        if blank {
+            if !previous_blank {
+                output.push(Line {
+                    text: String::new(),
+                    code: false,
+                });
+            }
+        } else {
+            output.push(line);
+        }
+    }
+    if output
+        .last()
+        .is_some_and(|last| !last.code && last.text.is_empty())
+    {
+        output.pop();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(markdown: &str) -> String {
+        postprocess(markdown, &Options::default())
+    }
+
+    #[test]
+    fn removes_empty_headings() {
+        assert_eq!(
+            run("# Title\n\n## \n\n## [](#anchor)\n\ntext"),
+            "# Title\n\ntext"
+        );
+        assert_eq!(run("# C#"), "# C#");
+    }
+
+    #[test]
+    fn ignores_empty_headings_when_normalizing() {
+        assert_eq!(run("## \n\n### A\n\n#### B"), "# A\n\n## B");
+        assert_eq!(run("# Title\n\n## \n\n### Sub"), "# Title\n\n## Sub");
+    }
+
+    #[test]
+    fn keeps_the_trailing_newline() {
+        assert_eq!(run("# Title\n\ntext\n"), "# Title\n\ntext\n");
+        assert_eq!(run("text"), "text");
+        assert_eq!(run("\n\n"), "");
+    }
+
+    #[test]
+    fn collapses_blank_lines_outside_code() {
+        assert_eq!(
+            run("\n\na\n\n\n\nb\n```\n\n\n\n```\n\n\n"),
+            "a\n\nb\n```\n\n\n\n```\n"
+        );
+    }
+
+    #[test]
+    fn normalizes_heading_levels() {
+        assert_eq!(
+            run("### Top\n##### Skipped\n#### Sub\n### Next"),
+            "# Top\n## Skipped\n## Sub\n# Next"
+        );
+        assert_eq!(
+            run("```\n### not a heading\n```"),
+            "```\n### not a heading\n```"
+        );
+    }
+
+    #[test]
+    fn dedupes_repeated_navigation() {
+        let nav = "- [Home](/)\n- [Docs](/docs)\n\n- [Blog](/blog)";
+        let markdown = format!("{nav}\n\n# Page\n\nBody [link](/x).\n\n{nav}\n\nEnd");
+        assert_eq!(
+            run(&markdown),
+            format!("{nav}\n\n# Page\n\nBody [link](/x).\n\nEnd")
+        );
+
+        let short = "[Prev](/1) | [Next](/3)\n\ntext\n\n[Prev](/1) | [Next](/3)";
+        assert_eq!(run(short), short);
+    }
+
+    #[test]
+    fn recognizes_link_only_lines() {
+        assert_eq!(link_only("* [![logo](/l.png)](/) [Home](/)"), Some(2));
+        assert_eq!(link_only("1. [a](/a(b)) · [c](/c)"), Some(2));
+        assert_eq!(link_only("See [a](/a)"), None);
+        assert_eq!(link_only(""), None);
+    }
+
+    #[test]
+    fn passes_can_be_disabled() {
+        let options = Options {
+            remove_empty_headings: false,
+            collapse_blank_lines: false,
+            dedupe_link_runs: false,
+            normalize_headings: false,
+            ..Options::default()
+        };
+        let markdown = "### a\n\n\n\n## \n";
+        assert_eq!(postprocess(markdown, &options), "### a\n\n\n\n## \n");
+    }
+}
@@ -0,0 +1,17 @@
+//! Node-API bindings, built with `--features napi`.
+
+use napi_derive::napi;
+
+use crate::{postprocess, Options};
+
+/// Cleans up `markdown`; `options` is the same optional JSON object the C ABI
+/// takes. Returns the markdown itself rather than a JSON envelope.
+#[napi(js_name = "postprocessMarkdown")]
+pub fn postprocess_markdown(markdown: String, options: Option<String>) -> napi::Result<String> {
+    let options = match options {
+        Some(options) => serde_json::from_str::<Options>(&options)
+            .map_err(|err| napi::Error::from_reason(format!("invalid options: {err}")))?,
+        None => Options::default(),
+    };
+    Ok(postprocess(&markdown, &options))
+}
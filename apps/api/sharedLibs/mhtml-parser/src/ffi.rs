@@ -0,0 +1,46 @@
+//! C ABI for the API. Results are JSON strings owned by Rust and must be
+//! handed back to [`mhtml_free_string`].
+
+use std::ffi::c_char;
+
+use ffi_support::{catch_panic, into_c_string};
+use serde_json::json;
+
+use crate::unpack;
+
+/// Unpacks the MHTML archive in `data[..len]` and returns the serialized
+/// [`crate::Archive`] (resource data base64 encoded when
+/// `include_resource_data` is set), or `{"error"}`.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes. The returned pointer must be
+/// released with [`mhtml_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn mhtml_unpack(
+    data: *const u8,
+    len: usize,
+    include_resource_data: bool,
+) -> *mut c_char {
+    catch_panic(|| {
+        let bytes = if data.is_null() {
+            &[][..]
+        } else {
+            std::slice::from_raw_parts(data, len)
+        };
+        let result = match unpack(bytes, include_resource_data) {
+            Ok(archive) => serde_json::to_string(&archive),
+            Err(err) => serde_json::to_string(&json!({ "error": err.to_string() })),
+        };
+        into_c_string(result.unwrap_or_else(|err| json!({ "error": err.to_string() }).to_string()))
+    })
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+/// `ptr` must come from a function of this library and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn mhtml_free_string(ptr: *mut c_char) {
+    ffi_support::free_string(ptr);
+}
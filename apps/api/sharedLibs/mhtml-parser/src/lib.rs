@@ -0,0 +1,259 @@
+//! Unpacks MHTML web archives (saved pages, `.mht`/`.mhtml`) into the main
+//! HTML document plus a map of the bundled resources. The HTML is meant to go
+//! through the regular HTML to markdown pipeline afterwards, exactly like a
+//! freshly scraped page.
+
+use base64::Engine;
+use serde::{Serialize, Serializer};
+
+mod ffi;
+mod mime;
+#[cfg(feature = "napi")]
+pub mod node;
+
+pub use ffi::{mhtml_free_string, mhtml_unpack};
+
+use mime::{Headers, Part};
+
+#[derive(Debug, thiserror::Error)]
+pub enum MhtmlError {
+    #[error("no header block found, not a MIME archive")]
+    NotMime,
+    #[error("multipart archive without a boundary parameter")]
+    MissingBoundary,
+    #[error("archive doesn't contain an html document")]
+    NoHtml,
+    #[error("multiparts nested more than {MAX_DEPTH} levels deep")]
+    TooDeep,
+}
+
+/// Saved pages nest at most two or three multiparts; the limit keeps crafted
+/// archives from recursing until the stack overflows.
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Serialize)]
+pub struct Archive {
+    /// Original URL of the page, from `Snapshot-Content-Location` or the
+    /// main part's `Content-Location`.
+    pub url: Option<String>,
+    pub subject: Option<String>,
+    pub date: Option<String>,
+    /// The main document, decoded to UTF-8. `cid:` references to bundled
+    /// resources are rewritten to their `Content-Location`.
+    pub html: String,
+    pub resources: Vec<Resource>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    pub location: Option<String>,
+    pub content_id: Option<String>,
+    pub content_type: String,
+    pub size: usize,
+    #[serde(
+        serialize_with = "serialize_base64",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub data: Option<Vec<u8>>,
+}
+
+fn serialize_base64<S: Serializer>(
+    data: &Option<Vec<u8>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match data {
+        Some(data) => {
+            serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(data))
+        }
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Unpacks an MHTML archive. Resource bodies are only kept when
+/// `include_resource_data` is set; the map itself is always returned.
+pub fn unpack(data: &[u8], include_resource_data: bool) -> Result<Archive, MhtmlError> {
+    let root = mime::parse_part(data).ok_or(MhtmlError::NotMime)?;
+    let mut leaves = Vec::new();
+    let start = root
+        .headers
+        .content_type_param("start")
+        .map(|start| start.trim_matches(['<', '>']).to_string());
+    collect_leaves(root, 0, &mut leaves)?;
+
+    let main_index = start
+        .as_deref()
+        .and_then(|start| {
+            leaves
+                .iter()
+                .position(|part| part.headers.content_id().as_deref() == Some(start))
+        })
+        .or_else(|| {
+            leaves
+                .iter()
+                .position(|part| part.headers.mime_type() == "text/html")
+        })
+        .ok_or(MhtmlError::NoHtml)?;
+
+    let mut resources = Vec::with_capacity(leaves.len().saturating_sub(1));
+    let mut main = None;
+    for (index, part) in leaves.into_iter().enumerate() {
+        if index == main_index {
+            main = Some(part);
+            continue;
+        }
+        let body = part.decoded_body();
+        resources.push(Resource {
+            location: part.headers.get("content-location").map(str::to_string),
+            content_id: part.headers.content_id(),
+            content_type: part.headers.mime_type(),
+            size: body.len(),
+            data: include_resource_data.then_some(body),
+        });
+    }
+    let main = main.ok_or(MhtmlError::NoHtml)?;
+
+    let mut html = main.decoded_text();
+    for resource in &resources {
+        if let (Some(id), Some(location)) = (&resource.content_id, &resource.location) {
+            html = html.replace(&format!("cid:{id}"), location);
+        }
+    }
+
+    Ok(Archive {
+        url: root_header(data, "snapshot-content-location")
+            .or_else(|| main.headers.get("content-location").map(str::to_string)),
+        subject: root_header(data, "subject"),
+        date: root_header(data, "date"),
+        html,
+        resources,
+    })
+}
+
+fn root_header(data: &[u8], name: &str) -> Option<String> {
+    let (headers, _) = mime::split_headers(data)?;
+    Headers::parse(headers)
+        .get(name)
+        .map(mime::decode_encoded_words)
+}
+
+/// Flattens nested multiparts (e.g. `multipart/alternative` inside
+/// `multipart/related`) into their leaf parts, in document order.
+fn collect_leaves<'a>(
+    part: Part<'a>,
+    depth: usize,
+    leaves: &mut Vec<Part<'a>>,
+) -> Result<(), MhtmlError> {
+    if !part.headers.mime_type().starts_with("multipart/") {
+        leaves.push(part);
+        return Ok(());
+    }
+    if depth == MAX_DEPTH {
+        return Err(MhtmlError::TooDeep);
+    }
+    let boundary = part
+        .headers
+        .content_type_param("boundary")
+        .ok_or(MhtmlError::MissingBoundary)?;
+    for child in mime::split_multipart(part.body, &boundary) {
+        if let Some(child) = mime::parse_part(child) {
+            collect_leaves(child, depth + 1, leaves)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARCHIVE: &str = "From: <Saved by Blink>\r\n\
+Snapshot-Content-Location: https://example.com/article\r\n\
+Subject: =?utf-8?Q?An_article?=\r\n\
+Date: Tue, 2 Jul 2024 10:00:00 -0000\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/related;\r\n\
+\ttype=\"text/html\";\r\n\
+\tboundary=\"----MultipartBoundary--abc----\"\r\n\
+\r\n\
+------MultipartBoundary--abc----\r\n\
+Content-Type: text/html; charset=iso-8859-1\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+Content-Location: https://example.com/article\r\n\
+\r\n\
+<html><body><h1>Caf=E9</h1><img src=3D\"cid:logo@example\"/><p>long line th=\r\n\
+at wraps</p></body></html>\r\n\
+------MultipartBoundary--abc----\r\n\
+Content-Type: image/png\r\n\
+Content-Transfer-Encoding: base64\r\n\
+Content-ID: <logo@example>\r\n\
+Content-Location: https://example.com/logo.png\r\n\
+\r\n\
+iVBORw==\r\n\
+------MultipartBoundary--abc------\r\n";
+
+    #[test]
+    fn unpacks_main_document_and_resources() {
+        let archive = unpack(ARCHIVE.as_bytes(), true).unwrap();
+        assert_eq!(archive.url.as_deref(), Some("https://example.com/article"));
+        assert_eq!(archive.subject.as_deref(), Some("An article"));
+        assert_eq!(
+            archive.date.as_deref(),
+            Some("Tue, 2 Jul 2024 10:00:00 -0000")
+        );
+        assert_eq!(
+            archive.html,
+            "<html><body><h1>Café</h1><img src=\"https://example.com/logo.png\"/><p>long line that wraps</p></body></html>"
+        );
+        assert_eq!(archive.resources.len(), 1);
+        let logo = &archive.resources[0];
+        assert_eq!(logo.content_type, "image/png");
+        assert_eq!(logo.content_id.as_deref(), Some("logo@example"));
+        assert_eq!(logo.data.as_deref(), Some(&b"\x89PNG"[..]));
+        assert_eq!(logo.size, 4);
+    }
+
+    #[test]
+    fn skips_resource_data_on_request() {
+        let archive = unpack(ARCHIVE.as_bytes(), false).unwrap();
+        assert!(archive.resources[0].data.is_none());
+        assert_eq!(archive.resources[0].size, 4);
+    }
+
+    #[test]
+    fn accepts_single_part_html() {
+        let archive = unpack(
+            b"Content-Type: text/html\nContent-Location: https://example.com/\n\n<p>hi</p>",
+            false,
+        )
+        .unwrap();
+        assert_eq!(archive.html, "<p>hi</p>");
+        assert_eq!(archive.url.as_deref(), Some("https://example.com/"));
+    }
+
+    #[test]
+    fn rejects_deeply_nested_multiparts() {
+        let mut archive = "Content-Type: text/html\n\n<p>hi</p>".to_string();
+        for depth in 0..100 {
+            archive = format!(
+                "Content-Type: multipart/related; boundary=\"b{depth}\"\n\n--b{depth}\n{archive}\n--b{depth}--\n"
+            );
+        }
+        assert!(matches!(
+            unpack(archive.as_bytes(), false),
+            Err(MhtmlError::TooDeep)
+        ));
+    }
+
+    #[test]
+    fn rejects_archives_without_html() {
+        assert!(matches!(
+            unpack(b"Content-Type: text/plain\n\nhello", false),
+            Err(MhtmlError::NoHtml)
+        ));
+        assert!(matches!(
+            unpack(b"garbage", false),
+            Err(MhtmlError::NotMime)
+        ));
+    }
+}
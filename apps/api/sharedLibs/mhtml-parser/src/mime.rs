@@ -0,0 +1,293 @@
+//! The subset of MIME (RFC 2045-2047) needed to read web archives.
+
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+use encoding_rs::{Encoding, UTF_8};
+
+/// Archives in the wild often drop the trailing padding.
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+pub(crate) struct Headers {
+    /// Lowercased names with unfolded values, in order.
+    entries: Vec<(String, String)>,
+}
+
+impl Headers {
+    pub fn parse(block: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(block);
+        let mut entries: Vec<(String, String)> = Vec::new();
+        for line in text.lines() {
+            if line.starts_with([' ', '\t']) {
+                if let Some((_, value)) = entries.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((name, value)) = line.split_once(':') {
+                entries.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+        Headers { entries }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Lowercased media type without parameters, `text/plain` by default.
+    pub fn mime_type(&self) -> String {
+        self.get("content-type")
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase())
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| "text/plain".to_string())
+    }
+
+    pub fn content_type_param(&self, name: &str) -> Option<String> {
+        let content_type = self.get("content-type")?;
+        split_params(content_type).find_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            key.trim()
+                .eq_ignore_ascii_case(name)
+                .then(|| value.trim().trim_matches('"').to_string())
+        })
+    }
+
+    pub fn content_id(&self) -> Option<String> {
+        self.get("content-id")
+            .map(|id| id.trim().trim_matches(['<', '>']).to_string())
+    }
+}
+
+/// Splits `type/subtype; a=1; b="x;y"` into its parameters, respecting quotes.
+fn split_params(value: &str) -> impl Iterator<Item = &str> {
+    let mut params = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    for (index, ch) in value.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                if let Some(start) = start {
+                    params.push(&value[start..index]);
+                }
+                start = Some(index + 1);
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = start {
+        params.push(&value[start..]);
+    }
+    params.into_iter()
+}
+
+pub(crate) struct Part<'a> {
+    pub headers: Headers,
+    pub body: &'a [u8],
+}
+
+impl Part<'_> {
+    /// Body with the content transfer encoding undone.
+    pub fn decoded_body(&self) -> Vec<u8> {
+        let encoding = self
+            .headers
+            .get("content-transfer-encoding")
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match encoding.as_str() {
+            "base64" => {
+                let compact: Vec<u8> = self
+                    .body
+                    .iter()
+                    .copied()
+                    .filter(|b| !b.is_ascii_whitespace())
+                    .collect();
+                BASE64
+                    .decode(&compact)
+                    .unwrap_or_else(|_| self.body.to_vec())
+            }
+            "quoted-printable" => decode_quoted_printable(self.body),
+            _ => self.body.to_vec(),
+        }
+    }
+
+    /// Decoded body converted from the declared charset to UTF-8.
+    pub fn decoded_text(&self) -> String {
+        let encoding = self
+            .headers
+            .content_type_param("charset")
+            .and_then(|label| Encoding::for_label(label.as_bytes()))
+            .unwrap_or(UTF_8);
+        encoding.decode(&self.decoded_body()).0.into_owned()
+    }
+}
+
+/// Splits off the header block at the first empty line.
+pub(crate) fn split_headers(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let crlf = find(data, b"\r\n\r\n").map(|index| (index, 4));
+    let lf = find(data, b"\n\n").map(|index| (index, 2));
+    let (index, separator) = match (crlf, lf) {
+        (Some(crlf), Some(lf)) => crlf.min(lf),
+        (Some(found), None) | (None, Some(found)) => found,
+        (None, None) => return None,
+    };
+    Some((&data[..index], &data[index + separator..]))
+}
+
+pub(crate) fn parse_part(data: &[u8]) -> Option<Part<'_>> {
+    let (headers, body) = split_headers(data)?;
+    Some(Part {
+        headers: Headers::parse(headers),
+        body,
+    })
+}
+
+/// Returns the raw parts between `--boundary` delimiter lines. The line break
+/// before each delimiter belongs to the delimiter, not to the part.
+pub(crate) fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{boundary}");
+    let delimiter = delimiter.as_bytes();
+    let mut positions = Vec::new();
+    let mut offset = 0;
+    while let Some(found) = find(&body[offset..], delimiter) {
+        let position = offset + found;
+        if position == 0 || body[position - 1] == b'\n' {
+            positions.push(position);
+        }
+        offset = position + delimiter.len();
+    }
+
+    let mut parts = Vec::new();
+    for window in positions.windows(2) {
+        let after_delimiter = &body[window[0] + delimiter.len()..window[1]];
+        if after_delimiter.starts_with(b"--") {
+            break;
+        }
+        let Some(line_end) = after_delimiter.iter().position(|b| *b == b'\n') else {
+            continue;
+        };
+        let mut part = &after_delimiter[line_end + 1..];
+        part = part.strip_suffix(b"\n").unwrap_or(part);
+        part = part.strip_suffix(b"\r").unwrap_or(part);
+        parts.push(part);
+    }
+    parts
+}
+
+fn decode_quoted_printable(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut index = 0;
+    while index < input.len() {
+        if input[index] != b'=' {
+            output.push(input[index]);
+            index += 1;
+            continue;
+        }
+        let rest = &input[index + 1..];
+        if rest.starts_with(b"\r\n") {
+            index += 3;
+        } else if rest.starts_with(b"\n") {
+            index += 2;
+        } else if let Some(byte) = rest
+            .get(..2)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            output.push(byte);
+            index += 3;
+        } else {
+            output.push(b'=');
+            index += 1;
+        }
+    }
+    output
+}
+
+/// Decodes RFC 2047 encoded words (`=?utf-8?Q?Caf=C3=A9?=`) in header values.
+/// Whitespace between two adjacent encoded words is dropped, as the RFC asks.
+pub(crate) fn decode_encoded_words(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut rest = value;
+    let mut previous_was_encoded = false;
+    while let Some(start) = rest.find("=?") {
+        let (before, candidate) = rest.split_at(start);
+        let decoded = parse_encoded_word(candidate);
+        if !(previous_was_encoded && decoded.is_some() && before.trim().is_empty()) {
+            output.push_str(before);
+        }
+        match decoded {
+            Some((text, consumed)) => {
+                output.push_str(&text);
+                rest = &candidate[consumed..];
+                previous_was_encoded = true;
+            }
+            None => {
+                output.push_str("=?");
+                rest = &candidate[2..];
+                previous_was_encoded = false;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+fn parse_encoded_word(candidate: &str) -> Option<(String, usize)> {
+    let (charset, rest) = candidate[2..].split_once('?')?;
+    let (encoding, rest) = rest.split_once('?')?;
+    let (text, rest) = rest.split_once("?=")?;
+    let bytes = match encoding {
+        "B" | "b" => BASE64.decode(text).ok()?,
+        "Q" | "q" => decode_quoted_printable(text.replace('_', " ").as_bytes()),
+        _ => return None,
+    };
+    let charset = Encoding::for_label(charset.as_bytes()).unwrap_or(UTF_8);
+    let consumed = candidate.len() - rest.len();
+    Some((charset.decode(&bytes).0.into_owned(), consumed))
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_encoded_words() {
+        assert_eq!(
+            decode_encoded_words("=?utf-8?Q?Caf=C3=A9?= menu"),
+            "Café menu"
+        );
+        assert_eq!(
+            decode_encoded_words("=?utf-8?B?SGVsbG8=?= =?utf-8?Q?_world?="),
+            "Hello world"
+        );
+        assert_eq!(decode_encoded_words("plain =? text"), "plain =? text");
+    }
+
+    #[test]
+    fn reads_quoted_parameters() {
+        let headers =
+            Headers::parse(b"Content-Type: multipart/related; boundary=\"a;b\"; type=text/html");
+        assert_eq!(
+            headers.content_type_param("boundary").as_deref(),
+            Some("a;b")
+        );
+        assert_eq!(
+            headers.content_type_param("type").as_deref(),
+            Some("text/html")
+        );
+        assert_eq!(headers.mime_type(), "multipart/related");
+    }
+}
@@ -0,0 +1,15 @@
+//! Node-API bindings, built with `--features napi`.
+
+use napi::bindgen_prelude::Buffer;
+use napi_derive::napi;
+
+use crate::unpack;
+
+/// Returns the unpacked archive as the same JSON string as the C ABI, but
+/// throws on failure instead of returning `{"error"}`.
+#[napi(js_name = "unpackMhtml")]
+pub fn unpack_mhtml(data: Buffer, include_resource_data: Option<bool>) -> napi::Result<String> {
+    let archive = unpack(&data, include_resource_data.unwrap_or(false))
+        .map_err(|err| napi::Error::from_reason(err.to_string()))?;
+    serde_json::to_string(&archive).map_err(|err| napi::Error::from_reason(err.to_string()))
+}
@@ -0,0 +1,369 @@
+//! Converts PowerPoint decks (.pptx) into ordered markdown sections, one per
+//! slide, with the slide title, bullet text, tables, speaker notes and the
+//! images placed on the slide.
+
+use std::collections::HashMap;
+
+use roxmltree::Document;
+use serde::Serialize;
+use zip_package::{image_content_type, resolve_part_path, serialize_base64, Package, PackageError};
+
+mod ffi;
+mod slide;
+
+pub use ffi::{pptx_free_string, pptx_to_markdown};
+
+const R_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PptxError {
+    #[error("invalid pptx archive: {0}")]
+    Archive(#[from] PackageError),
+    #[error("malformed xml in {part}: {source}")]
+    Xml {
+        part: String,
+        source: roxmltree::Error,
+    },
+    #[error("missing {0}, not a presentation")]
+    MissingPart(String),
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ParsedPptx {
+    /// All slides rendered as `## title` sections, in presentation order.
+    pub markdown: String,
+    pub slides: Vec<Slide>,
+    pub images: Vec<EmbeddedImage>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Slide {
+    /// 1-based position in the deck, hidden slides included.
+    pub number: usize,
+    pub title: Option<String>,
+    /// Slide body without the title heading.
+    pub markdown: String,
+    pub notes: Option<String>,
+    /// Package paths of the images on this slide.
+    pub images: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedImage {
+    pub name: String,
+    pub content_type: String,
+    #[serde(serialize_with = "serialize_base64")]
+    pub data: Vec<u8>,
+}
+
+/// Parses a .pptx file from memory.
+pub fn parse_pptx(data: &[u8]) -> Result<ParsedPptx, PptxError> {
+    let mut package = Package::open(data)?;
+
+    let presentation = package
+        .read_string("ppt/presentation.xml")?
+        .ok_or_else(|| PptxError::MissingPart("ppt/presentation.xml".to_string()))?;
+    let presentation_rels = relationships(&mut package, "ppt/presentation.xml")?;
+    let slide_paths = slide_order(&presentation, &presentation_rels)?;
+
+    let mut parsed = ParsedPptx::default();
+    for (index, path) in slide_paths.iter().enumerate() {
+        let Some(xml) = package.read_string(path)? else {
+            continue;
+        };
+        let rels = relationships(&mut package, path)?;
+        let content = slide::render_slide(&xml, &rels, path)?;
+
+        let notes = match rels.values().find(|rel| rel.kind == "notesSlide") {
+            Some(rel) => match package.read_string(&rel.target)? {
+                Some(notes_xml) => slide::render_notes(&notes_xml, &rel.target)?,
+                None => None,
+            },
+            None => None,
+        };
+
+        for image in &content.images {
+            if parsed.images.iter().any(|existing| &existing.name == image) {
+                continue;
+            }
+            if let Some(data) = package.read_bytes(image)? {
+                parsed.images.push(EmbeddedImage {
+                    name: image.clone(),
+                    content_type: image_content_type(image).to_string(),
+                    data,
+                });
+            }
+        }
+
+        parsed.slides.push(Slide {
+            number: index + 1,
+            title: content.title,
+            markdown: content.markdown,
+            notes,
+            images: content.images,
+        });
+    }
+
+    parsed.markdown = render_deck(&parsed.slides);
+    Ok(parsed)
+}
+
+fn render_deck(slides: &[Slide]) -> String {
+    let mut sections = Vec::with_capacity(slides.len());
+    for slide in slides {
+        let mut section = match &slide.title {
+            Some(title) => format!("## {title}"),
+            None => format!("## Slide {}", slide.number),
+        };
+        if !slide.markdown.is_empty() {
+            section.push_str("\n\n");
+            section.push_str(&slide.markdown);
+        }
+        if let Some(notes) = &slide.notes {
+            section.push_str("\n\n> **Notes:** ");
+            section.push_str(&notes.replace('\n', "\n> "));
+        }
+        sections.push(section);
+    }
+    let mut markdown = sections.join("\n\n");
+    if !markdown.is_empty() {
+        markdown.push('\n');
+    }
+    markdown
+}
+
+/// Slides are ordered by `p:sldIdLst` in presentation.xml, not by their file
+/// names, which keep the numbers they were created with.
+fn slide_order(
+    presentation: &str,
+    rels: &HashMap<String, Relationship>,
+) -> Result<Vec<String>, PptxError> {
+    let doc = parse_xml(presentation, "ppt/presentation.xml")?;
+    Ok(doc
+        .descendants()
+        .filter(|node| node.tag_name().name() == "sldId")
+        .filter_map(|node| node.attribute((R_NS, "id")))
+        .filter_map(|id| rels.get(id))
+        .map(|rel| rel.target.clone())
+        .collect())
+}
+
+pub(crate) fn parse_xml<'a>(xml: &'a str, part: &str) -> Result<Document<'a>, PptxError> {
+    Document::parse(xml).map_err(|source| PptxError::Xml {
+        part: part.to_string(),
+        source,
+    })
+}
+
+#[derive(Debug)]
+pub(crate) struct Relationship {
+    /// Last segment of the relationship type URI, e.g. `image` or `notesSlide`.
+    pub kind: String,
+    /// Package path for internal targets, the raw URL for external ones.
+    pub target: String,
+    pub external: bool,
+}
+
+/// Reads the `_rels` part that belongs to `part`.
+fn relationships(
+    package: &mut Package,
+    part: &str,
+) -> Result<HashMap<String, Relationship>, PptxError> {
+    let (directory, file) = part.rsplit_once('/').unwrap_or(("", part));
+    let rels_path = format!("{directory}/_rels/{file}.rels");
+    let Some(xml) = package.read_string(&rels_path)? else {
+        return Ok(HashMap::new());
+    };
+    let doc = parse_xml(&xml, &rels_path)?;
+    let mut relationships = HashMap::new();
+    for rel in doc
+        .descendants()
+        .filter(|node| node.has_tag_name("Relationship"))
+    {
+        let (Some(id), Some(target)) = (rel.attribute("Id"), rel.attribute("Target")) else {
+            continue;
+        };
+        let external = rel.attribute("TargetMode") == Some("External");
+        let kind = rel
+            .attribute("Type")
+            .and_then(|kind| kind.rsplit('/').next())
+            .unwrap_or_default()
+            .to_string();
+        let target = if external {
+            target.to_string()
+        } else {
+            resolve_part_path(directory, target)
+        };
+        relationships.insert(
+            id.to_string(),
+            Relationship {
+                kind,
+                target,
+                external,
+            },
+        );
+    }
+    Ok(relationships)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+
+    const NS: &str = r#"xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships""#;
+
+    fn rels(entries: &[(&str, &str, &str)]) -> Vec<u8> {
+        let body: String = entries
+            .iter()
+            .map(|(id, kind, target)| {
+                format!(r#"<Relationship Id="{id}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/{kind}" Target="{target}"/>"#)
+            })
+            .collect();
+        format!(r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{body}</Relationships>"#).into_bytes()
+    }
+
+    fn build_pptx(parts: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in parts {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn shape(placeholder: Option<&str>, paragraphs: &str) -> String {
+        let ph = placeholder
+            .map(|kind| format!(r#"<p:ph type="{kind}"/>"#))
+            .unwrap_or_default();
+        format!("<p:sp><p:nvSpPr><p:cNvPr id=\"1\" name=\"s\"/><p:cNvSpPr/><p:nvPr>{ph}</p:nvPr></p:nvSpPr><p:txBody>{paragraphs}</p:txBody></p:sp>")
+    }
+
+    fn slide(shapes: &str) -> Vec<u8> {
+        format!("<p:sld {NS}><p:cSld><p:spTree>{shapes}</p:spTree></p:cSld></p:sld>").into_bytes()
+    }
+
+    #[test]
+    fn renders_slides_in_presentation_order() {
+        let presentation = format!(
+            r#"<p:presentation {NS}><p:sldIdLst><p:sldId id="256" r:id="rId2"/><p:sldId id="257" r:id="rId1"/></p:sldIdLst></p:presentation>"#
+        );
+        let first = slide(&format!(
+            "{}{}",
+            shape(Some("title"), "<a:p><a:r><a:t>Roadmap</a:t></a:r></a:p>"),
+            shape(
+                Some("body"),
+                r#"<a:p><a:r><a:t>Ship v1</a:t></a:r></a:p><a:p><a:pPr lvl="1"/><a:r><a:t>Docs</a:t></a:r></a:p>"#
+            )
+        ));
+        let second = slide(&format!(
+            "{}<p:pic><p:nvPicPr><p:cNvPr id=\"2\" name=\"Picture\" descr=\"Chart\"/></p:nvPicPr><p:blipFill><a:blip r:embed=\"rId2\"/></p:blipFill></p:pic>",
+            shape(None, "<a:p><a:r><a:t>Free text</a:t></a:r></a:p>")
+        ));
+        let notes = format!(
+            "<p:notes {NS}><p:cSld><p:spTree>{}{}</p:spTree></p:cSld></p:notes>",
+            shape(Some("sldImg"), ""),
+            shape(Some("body"), "<a:p><a:r><a:t>Say hi</a:t></a:r></a:p>")
+        );
+
+        let pptx = build_pptx(&[
+            ("ppt/presentation.xml", presentation.into_bytes()),
+            (
+                "ppt/_rels/presentation.xml.rels",
+                rels(&[
+                    ("rId1", "slide", "slides/slide1.xml"),
+                    ("rId2", "slide", "slides/slide2.xml"),
+                ]),
+            ),
+            ("ppt/slides/slide1.xml", second),
+            (
+                "ppt/slides/_rels/slide1.xml.rels",
+                rels(&[
+                    ("rId1", "notesSlide", "../notesSlides/notesSlide1.xml"),
+                    ("rId2", "image", "../media/image1.png"),
+                ]),
+            ),
+            ("ppt/notesSlides/notesSlide1.xml", notes.into_bytes()),
+            ("ppt/slides/slide2.xml", first),
+            ("ppt/media/image1.png", b"\x89PNG".to_vec()),
+        ]);
+
+        let parsed = parse_pptx(&pptx).unwrap();
+        assert_eq!(
+            parsed.markdown,
+            "## Roadmap\n\n- Ship v1\n  - Docs\n\n## Slide 2\n\nFree text\n\n![Chart](ppt/media/image1.png)\n\n> **Notes:** Say hi\n"
+        );
+        assert_eq!(parsed.slides[0].title.as_deref(), Some("Roadmap"));
+        assert_eq!(parsed.slides[1].notes.as_deref(), Some("Say hi"));
+        assert_eq!(parsed.slides[1].images, ["ppt/media/image1.png"]);
+        assert_eq!(parsed.images.len(), 1);
+        assert_eq!(parsed.images[0].content_type, "image/png");
+    }
+
+    #[test]
+    fn renders_tables() {
+        let cell = |text: &str| {
+            format!("<a:tc><a:txBody><a:p><a:r><a:t>{text}</a:t></a:r></a:p></a:txBody></a:tc>")
+        };
+        let table = format!(
+            "<p:graphicFrame><a:graphic><a:graphicData><a:tbl><a:tr>{}{}</a:tr><a:tr>{}{}</a:tr></a:tbl></a:graphicData></a:graphic></p:graphicFrame>",
+            cell("Quarter"),
+            cell("Revenue"),
+            cell("Q1"),
+            cell("10")
+        );
+        let pptx = build_pptx(&[
+            (
+                "ppt/presentation.xml",
+                format!(r#"<p:presentation {NS}><p:sldIdLst><p:sldId id="256" r:id="rId1"/></p:sldIdLst></p:presentation>"#).into_bytes(),
+            ),
+            (
+                "ppt/_rels/presentation.xml.rels",
+                rels(&[("rId1", "slide", "slides/slide1.xml")]),
+            ),
+            ("ppt/slides/slide1.xml", slide(&table)),
+        ]);
+        assert_eq!(
+            parse_pptx(&pptx).unwrap().slides[0].markdown,
+            "| Quarter | Revenue |\n| --- | --- |\n| Q1 | 10 |"
+        );
+    }
+
+    #[test]
+    fn clamps_out_of_range_list_levels() {
+        let pptx = build_pptx(&[
+            (
+                "ppt/presentation.xml",
+                format!(r#"<p:presentation {NS}><p:sldIdLst><p:sldId id="256" r:id="rId1"/></p:sldIdLst></p:presentation>"#).into_bytes(),
+            ),
+            (
+                "ppt/_rels/presentation.xml.rels",
+                rels(&[("rId1", "slide", "slides/slide1.xml")]),
+            ),
+            (
+                "ppt/slides/slide1.xml",
+                slide(&shape(
+                    Some("body"),
+                    r#"<a:p><a:pPr lvl="18446744073709551615"/><a:r><a:t>Deep</a:t></a:r></a:p><a:p><a:pPr lvl="12"/><a:r><a:t>Deeper</a:t></a:r></a:p>"#,
+                )),
+            ),
+        ]);
+        assert_eq!(
+            parse_pptx(&pptx).unwrap().slides[0].markdown,
+            format!("- Deep\n{}- Deeper", "  ".repeat(8))
+        );
+    }
+
+    #[test]
+    fn rejects_non_presentations() {
+        let archive = build_pptx(&[("word/document.xml", b"<w/>".to_vec())]);
+        assert!(matches!(
+            parse_pptx(&archive),
+            Err(PptxError::MissingPart(_))
+        ));
+    }
+}
@@ -0,0 +1,245 @@
+//! Renders the shape tree of a slide (or notes page) to markdown.
+
+use std::collections::HashMap;
+
+use roxmltree::Node;
+
+use crate::{parse_xml, PptxError, Relationship, R_NS};
+
+const P_NS: &str = "http://schemas.openxmlformats.org/presentationml/2006/main";
+const A_NS: &str = "http://schemas.openxmlformats.org/drawingml/2006/main";
+
+pub(crate) struct SlideContent {
+    pub title: Option<String>,
+    pub markdown: String,
+    pub images: Vec<String>,
+}
+
+struct Line {
+    markdown: String,
+    list_item: bool,
+}
+
+pub(crate) fn render_slide(
+    xml: &str,
+    rels: &HashMap<String, Relationship>,
+    part: &str,
+) -> Result<SlideContent, PptxError> {
+    let doc = parse_xml(xml, part)?;
+    let mut content = SlideContent {
+        title: None,
+        markdown: String::new(),
+        images: Vec::new(),
+    };
+    let mut blocks = Vec::new();
+    if let Some(tree) = doc.descendants().find(|n| is(*n, P_NS, "spTree")) {
+        render_tree(tree, rels, &mut content, &mut blocks);
+    }
+    content.markdown = blocks.join("\n\n");
+    Ok(content)
+}
+
+/// Speaker notes live in the body placeholder of the notes slide; the other
+/// placeholders hold the slide thumbnail and page number.
+pub(crate) fn render_notes(xml: &str, part: &str) -> Result<Option<String>, PptxError> {
+    let doc = parse_xml(xml, part)?;
+    let notes: Vec<String> = doc
+        .descendants()
+        .filter(|n| is(*n, P_NS, "sp") && placeholder_type(*n) == Some("body"))
+        .flat_map(|shape| paragraphs(shape).map(|p| paragraph_text(p)))
+        .filter(|text| !text.is_empty())
+        .collect();
+    Ok((!notes.is_empty()).then(|| notes.join("\n")))
+}
+
+fn render_tree(
+    tree: Node,
+    rels: &HashMap<String, Relationship>,
+    content: &mut SlideContent,
+    blocks: &mut Vec<String>,
+) {
+    for child in tree.children().filter(Node::is_element) {
+        if is(child, P_NS, "sp") {
+            match placeholder_type(child) {
+                Some("title" | "ctrTitle") if content.title.is_none() => {
+                    let title: Vec<String> = paragraphs(child)
+                        .map(paragraph_text)
+                        .filter(|text| !text.is_empty())
+                        .collect();
+                    if !title.is_empty() {
+                        content.title = Some(title.join(" "));
+                    }
+                }
+                placeholder => {
+                    let block = render_text_shape(child, placeholder, is_placeholder(child));
+                    if !block.is_empty() {
+                        blocks.push(block);
+                    }
+                }
+            }
+        } else if is(child, P_NS, "grpSp") {
+            render_tree(child, rels, content, blocks);
+        } else if is(child, P_NS, "pic") {
+            if let Some(block) = render_picture(child, rels, content) {
+                blocks.push(block);
+            }
+        } else if is(child, P_NS, "graphicFrame") {
+            if let Some(table) = child.descendants().find(|n| is(*n, A_NS, "tbl")) {
+                let block = render_table(table);
+                if !block.is_empty() {
+                    blocks.push(block);
+                }
+            }
+        }
+    }
+}
+
+/// Body placeholders are bulleted by default, free text boxes are not;
+/// explicit `a:buChar`/`a:buAutoNum`/`a:buNone` override either.
+fn render_text_shape(shape: Node, placeholder: Option<&str>, has_placeholder: bool) -> String {
+    let bulleted_by_default = has_placeholder && matches!(placeholder, None | Some("body" | "obj"));
+    let mut lines: Vec<Line> = Vec::new();
+    for paragraph in paragraphs(shape) {
+        let text = paragraph_text(paragraph);
+        if text.is_empty() {
+            continue;
+        }
+        let properties = paragraph.children().find(|n| is(*n, A_NS, "pPr"));
+        // OOXML allows levels 0 to 8; anything else is clamped rather than
+        // trusted as an indent width
+        let level = properties
+            .and_then(|p| p.attribute("lvl"))
+            .and_then(|lvl| lvl.parse::<u8>().ok())
+            .unwrap_or(0)
+            .min(8);
+        let bullet = properties.and_then(|p| {
+            p.children().find_map(|n| match n.tag_name().name() {
+                "buChar" => Some("-"),
+                "buAutoNum" => Some("1."),
+                "buNone" => Some(""),
+                _ => None,
+            })
+        });
+        let marker = match bullet {
+            Some(marker) => marker,
+            None if bulleted_by_default => "-",
+            None => "",
+        };
+        lines.push(if marker.is_empty() {
+            Line {
+                markdown: text,
+                list_item: false,
+            }
+        } else {
+            Line {
+                markdown: format!("{}{} {}", "  ".repeat(level as usize), marker, text),
+                list_item: true,
+            }
+        });
+    }
+
+    let mut markdown = String::new();
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            let both_list_items = line.list_item && lines[index - 1].list_item;
+            markdown.push_str(if both_list_items { "\n" } else { "\n\n" });
+        }
+        markdown.push_str(&line.markdown);
+    }
+    markdown
+}
+
+fn render_picture(
+    picture: Node,
+    rels: &HashMap<String, Relationship>,
+    content: &mut SlideContent,
+) -> Option<String> {
+    let embed = picture
+        .descendants()
+        .find(|n| is(*n, A_NS, "blip"))?
+        .attribute((R_NS, "embed"))?;
+    let rel = rels.get(embed).filter(|rel| !rel.external)?;
+    let alt = picture
+        .descendants()
+        .find(|n| is(*n, P_NS, "cNvPr"))
+        .and_then(|n| n.attribute("descr"))
+        .unwrap_or_default();
+    if !content.images.contains(&rel.target) {
+        content.images.push(rel.target.clone());
+    }
+    Some(format!("![{}]({})", alt, rel.target))
+}
+
+fn render_table(table: Node) -> String {
+    let rows: Vec<Vec<String>> = table
+        .children()
+        .filter(|n| is(*n, A_NS, "tr"))
+        .map(|row| {
+            row.children()
+                .filter(|n| is(*n, A_NS, "tc"))
+                .map(|cell| {
+                    paragraphs(cell)
+                        .map(paragraph_text)
+                        .filter(|text| !text.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                        .replace('|', "\\|")
+                })
+                .collect()
+        })
+        .collect();
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return String::new();
+    }
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    for (index, row) in rows.iter().enumerate() {
+        let mut cells = row.clone();
+        cells.resize(columns, String::new());
+        lines.push(format!("| {} |", cells.join(" | ")));
+        if index == 0 {
+            lines.push(format!("|{}", " --- |".repeat(columns)));
+        }
+    }
+    lines.join("\n")
+}
+
+fn paragraphs<'a, 'input>(node: Node<'a, 'input>) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.descendants().filter(|n| is(*n, A_NS, "p"))
+}
+
+fn paragraph_text(paragraph: Node) -> String {
+    let mut text = String::new();
+    for node in paragraph.descendants() {
+        if is(node, A_NS, "t") {
+            text.push_str(node.text().unwrap_or_default());
+        } else if is(node, A_NS, "br") {
+            text.push(' ');
+        }
+    }
+    text.trim().to_string()
+}
+
+fn is_placeholder(shape: Node) -> bool {
+    placeholder(shape).is_some()
+}
+
+fn placeholder_type<'a>(shape: Node<'a, '_>) -> Option<&'a str> {
+    placeholder(shape)?.attribute("type")
+}
+
+fn placeholder<'a, 'input>(shape: Node<'a, 'input>) -> Option<Node<'a, 'input>> {
+    shape
+        .children()
+        .find(|n| is(*n, P_NS, "nvSpPr"))?
+        .children()
+        .find(|n| is(*n, P_NS, "nvPr"))?
+        .children()
+        .find(|n| is(*n, P_NS, "ph"))
+}
+
+fn is(node: Node, namespace: &str, name: &str) -> bool {
+    node.is_element()
+        && node.tag_name().name() == name
+        && node.tag_name().namespace() == Some(namespace)
+}
@@ -0,0 +1,108 @@
+//! C ABI for the API. Results are JSON strings owned by Rust and must be
+//! handed back to [`spreadsheet_free_string`].
+
+use std::ffi::{c_char, CStr};
+
+use crate::{parse_spreadsheet, Format, Limits};
+use ffi_support::{catch_panic, error_json, into_c_string};
+
+/// Parses the spreadsheet in `data[..len]` and returns the serialized
+/// [`crate::Workbook`], or `{"error"}`.
+///
+/// `extension` is an optional file extension hint (`"xlsx"`, `"csv"`, ...);
+/// the format is sniffed from the content when it is null or unknown.
+/// `options` is an optional JSON object overriding [`Limits`]
+/// (`{"maxRows": 500}`).
+///
+/// # Safety
+/// `data` must point to `len` readable bytes, and `extension`/`options`
+/// must be null or valid NUL-terminated strings. The returned pointer must be
+/// released with [`spreadsheet_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn spreadsheet_to_json(
+    data: *const u8,
+    len: usize,
+    extension: *const c_char,
+    options: *const c_char,
+) -> *mut c_char {
+    catch_panic(|| {
+        let bytes = if data.is_null() {
+            &[][..]
+        } else {
+            std::slice::from_raw_parts(data, len)
+        };
+        let format = optional_str(extension).and_then(Format::from_extension);
+        let limits = match optional_str(options).map(serde_json::from_str::<Limits>) {
+            None => Limits::default(),
+            Some(Ok(limits)) => limits,
+            Some(Err(err)) => return error_json(format!("invalid options: {err}")),
+        };
+
+        match parse_spreadsheet(bytes, format, &limits)
+            .map(|workbook| serde_json::to_string(&workbook))
+        {
+            Ok(Ok(json)) => into_c_string(json),
+            Ok(Err(err)) => error_json(err.to_string()),
+            Err(err) => error_json(err.to_string()),
+        }
+    })
+}
+
+unsafe fn optional_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        None
+    } else {
+        CStr::from_ptr(ptr).to_str().ok()
+    }
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+/// `ptr` must come from a function of this library and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn spreadsheet_free_string(ptr: *mut c_char) {
+    ffi_support::free_string(ptr);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::ffi::CString;
+
+    fn call(data: &[u8], extension: Option<&str>, options: Option<&str>) -> serde_json::Value {
+        let extension = extension.map(|e| CString::new(e).unwrap());
+        let options = options.map(|o| CString::new(o).unwrap());
+        unsafe {
+            let ptr = spreadsheet_to_json(
+                data.as_ptr(),
+                data.len(),
+                extension.as_ref().map_or(std::ptr::null(), |e| e.as_ptr()),
+                options.as_ref().map_or(std::ptr::null(), |o| o.as_ptr()),
+            );
+            let value = serde_json::from_str(CStr::from_ptr(ptr).to_str().unwrap()).unwrap();
+            spreadsheet_free_string(ptr);
+            value
+        }
+    }
+
+    #[test]
+    fn honours_options() {
+        let value = call(b"a\n1\n2\n", Some("csv"), Some(r#"{"maxRows":1}"#));
+        assert_eq!(value["sheets"][0]["rows"], json!([{ "a": "1" }]));
+        assert_eq!(value["sheets"][0]["truncated"], json!(true));
+    }
+
+    #[test]
+    fn reports_errors_as_json() {
+        let value = call(b"PK\x03\x04garbage", Some("xlsx"), None);
+        assert!(value["error"].is_string());
+        let value = call(b"a\n1\n", None, Some("{"));
+        assert!(value["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid options"));
+    }
+}
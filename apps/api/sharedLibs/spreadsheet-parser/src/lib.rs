@@ -0,0 +1,479 @@
+//! Converts spreadsheets (.xlsx, .xlsb, .xls, .ods and .csv) into per-sheet
+//! JSON rows and markdown tables, so data files linked from pages can be
+//! scraped. CSV is streamed and only read up to the row cap. Workbook sheets
+//! are decoded whole by calamine and only the capped rows are converted, so
+//! zip based workbooks (.xlsx, .xlsb, .ods) are first checked against the
+//! decompression limits of `zip-package`.
+
+use std::io::Cursor;
+
+use calamine::{open_workbook_auto_from_rs, Data, Reader};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use zip_package::{Package, PackageError};
+
+mod ffi;
+
+pub use ffi::{spreadsheet_free_string, spreadsheet_to_json};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SpreadsheetError {
+    #[error("unsupported or corrupted workbook: {0}")]
+    Workbook(#[from] calamine::Error),
+    #[error("invalid csv: {0}")]
+    Csv(#[from] csv::Error),
+    #[error(transparent)]
+    Package(#[from] PackageError),
+}
+
+const ZIP: &[u8] = b"PK\x03\x04";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Any workbook format calamine understands, detected from the content.
+    Workbook,
+    Csv,
+}
+
+impl Format {
+    /// Maps a file extension (as found in the scraped URL) to a format.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension
+            .trim_start_matches('.')
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "xlsx" | "xlsm" | "xlsb" | "xls" | "xla" | "ods" => Some(Format::Workbook),
+            "csv" | "tsv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+
+    /// Sniffs the format from the file signature. Workbooks are either zip
+    /// (xlsx, xlsb, ods) or OLE (xls) containers; anything else is read as CSV.
+    pub fn detect(data: &[u8]) -> Self {
+        const OLE: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+        if data.starts_with(ZIP) || data.starts_with(OLE) {
+            Format::Workbook
+        } else {
+            Format::Csv
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Limits {
+    pub max_sheets: usize,
+    /// Data rows per sheet, not counting the header row.
+    pub max_rows: usize,
+    pub max_columns: usize,
+    pub max_cell_chars: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_sheets: 20,
+            max_rows: 1000,
+            max_columns: 50,
+            max_cell_chars: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Workbook {
+    pub sheets: Vec<Sheet>,
+    /// True when sheets were dropped because of `max_sheets`.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sheet {
+    pub name: String,
+    pub headers: Vec<String>,
+    /// One object per data row, keyed by header.
+    pub rows: Vec<Map<String, Value>>,
+    pub markdown: String,
+    /// Data rows emitted in `rows`. Blank rows are skipped and not counted.
+    pub total_rows: usize,
+    /// True when rows, columns or cell contents were cut by the limits.
+    pub truncated: bool,
+}
+
+/// Parses a spreadsheet from memory, detecting the format when not given.
+pub fn parse_spreadsheet(
+    data: &[u8],
+    format: Option<Format>,
+    limits: &Limits,
+) -> Result<Workbook, SpreadsheetError> {
+    match format.unwrap_or_else(|| Format::detect(data)) {
+        Format::Workbook => parse_workbook(data, limits),
+        Format::Csv => {
+            let sheet = build_sheet("Sheet1".to_string(), read_csv(data, limits), limits)?;
+            Ok(Workbook {
+                sheets: vec![sheet],
+                truncated: false,
+            })
+        }
+    }
+}
+
+fn parse_workbook(data: &[u8], limits: &Limits) -> Result<Workbook, SpreadsheetError> {
+    if data.starts_with(ZIP) {
+        Package::open(data)?.check_limits()?;
+    }
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(data))?;
+    let names = workbook.sheet_names();
+    let mut sheets = Vec::new();
+    for name in names.iter().take(limits.max_sheets) {
+        let range = workbook.worksheet_range(name)?;
+        let rows = range.rows().map(|row| {
+            Ok(row
+                .iter()
+                .take(limits.max_columns.saturating_add(1))
+                .map(cell_value)
+                .collect())
+        });
+        sheets.push(build_sheet(name.clone(), rows, limits)?);
+    }
+    Ok(Workbook {
+        sheets,
+        truncated: names.len() > limits.max_sheets,
+    })
+}
+
+fn cell_value(cell: &Data) -> Value {
+    match cell {
+        Data::Int(value) => Value::from(*value),
+        Data::Float(value) => serde_json::Number::from_f64(*value)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        Data::String(value) => Value::String(value.clone()),
+        Data::Bool(value) => Value::Bool(*value),
+        Data::DateTime(value) => match value.as_datetime() {
+            Some(datetime) => Value::String(datetime.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            None => Value::from(value.as_f64()),
+        },
+        Data::DateTimeIso(value) | Data::DurationIso(value) => Value::String(value.clone()),
+        Data::Error(error) => Value::String(error.to_string()),
+        Data::Empty => Value::Null,
+    }
+}
+
+/// Lazily yields CSV rows, each cut one cell past `max_columns` so the
+/// overflow is still detected.
+fn read_csv<'a>(
+    data: &'a [u8],
+    limits: &Limits,
+) -> impl Iterator<Item = Result<Vec<Value>, SpreadsheetError>> + 'a {
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    let columns = limits.max_columns.saturating_add(1);
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(sniff_delimiter(data))
+        .from_reader(data)
+        .into_byte_records()
+        .map(move |record| {
+            Ok(record?
+                .iter()
+                .take(columns)
+                .map(|field| match String::from_utf8_lossy(field) {
+                    field if field.is_empty() => Value::Null,
+                    field => Value::String(field.into_owned()),
+                })
+                .collect())
+        })
+}
+
+/// Picks the delimiter that splits the first line into the most fields.
+/// European exports commonly use `;`, and `.tsv` files get served as CSV.
+fn sniff_delimiter(data: &[u8]) -> u8 {
+    let first_line = data.split(|b| *b == b'\n').next().unwrap_or_default();
+    [b',', b';', b'\t', b'|']
+        .into_iter()
+        .max_by_key(|delimiter| first_line.iter().filter(|b| *b == delimiter).count())
+        .filter(|delimiter| first_line.contains(delimiter))
+        .unwrap_or(b',')
+}
+
+/// Reads rows until `max_rows` data rows are collected, plus the one after
+/// to tell whether the sheet was cut, and renders them.
+fn build_sheet(
+    name: String,
+    rows: impl IntoIterator<Item = Result<Vec<Value>, SpreadsheetError>>,
+    limits: &Limits,
+) -> Result<Sheet, SpreadsheetError> {
+    let is_blank = |row: &[Value]| row.iter().all(Value::is_null);
+    let mut rows = rows.into_iter();
+    let mut header_row = Vec::new();
+    for row in rows.by_ref() {
+        let row = row?;
+        if !is_blank(&row) {
+            header_row = row;
+            break;
+        }
+    }
+    let mut data_rows = Vec::new();
+    let mut truncated = false;
+    for row in rows {
+        let row = row?;
+        if is_blank(&row) {
+            continue;
+        }
+        if data_rows.len() == limits.max_rows {
+            truncated = true;
+            break;
+        }
+        data_rows.push(row);
+    }
+
+    let width = std::iter::once(&header_row)
+        .chain(&data_rows)
+        .map(|row| {
+            row.iter()
+                .rposition(|value| !value.is_null())
+                .map_or(0, |last| last + 1)
+        })
+        .max()
+        .unwrap_or(0);
+    truncated |= width > limits.max_columns;
+    let width = width.min(limits.max_columns);
+
+    for value in header_row.iter_mut().take(width) {
+        truncated |= truncate_cell(value, limits.max_cell_chars);
+    }
+    let headers = unique_headers(&header_row, width);
+    let mut json_rows = Vec::with_capacity(data_rows.len());
+    let mut table = vec![headers.clone()];
+    for row in &data_rows {
+        let mut object = Map::with_capacity(width);
+        let mut cells = Vec::with_capacity(width);
+        for (index, header) in headers.iter().enumerate() {
+            let mut value = row.get(index).cloned().unwrap_or(Value::Null);
+            truncated |= truncate_cell(&mut value, limits.max_cell_chars);
+            cells.push(display(&value));
+            object.insert(header.clone(), value);
+        }
+        json_rows.push(object);
+        table.push(cells);
+    }
+
+    Ok(Sheet {
+        name,
+        markdown: markdown_table(&table),
+        headers,
+        total_rows: json_rows.len(),
+        rows: json_rows,
+        truncated,
+    })
+}
+
+/// Cuts text cells to `max_chars`, returning whether anything was cut.
+fn truncate_cell(value: &mut Value, max_chars: usize) -> bool {
+    if let Value::String(text) = value {
+        if let Some((cut, _)) = text.char_indices().nth(max_chars) {
+            text.truncate(cut);
+            return true;
+        }
+    }
+    false
+}
+
+/// Uses the first row as headers, falling back to the column letter for
+/// blank cells and suffixing duplicates so no JSON key is overwritten.
+fn unique_headers(header_row: &[Value], width: usize) -> Vec<String> {
+    let mut headers: Vec<String> = Vec::with_capacity(width);
+    for index in 0..width {
+        let base = match header_row.get(index).map(display) {
+            Some(text) if !text.trim().is_empty() => text.trim().to_string(),
+            _ => column_name(index),
+        };
+        let mut header = base.clone();
+        let mut suffix = 2;
+        while headers.contains(&header) {
+            header = format!("{base}_{suffix}");
+            suffix += 1;
+        }
+        headers.push(header);
+    }
+    headers
+}
+
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn markdown_table(table: &[Vec<String>]) -> String {
+    let Some(header) = table.first().filter(|header| !header.is_empty()) else {
+        return String::new();
+    };
+    let escape = |cell: &String| {
+        cell.replace('|', "\\|")
+            .replace("\r\n", "<br>")
+            .replace('\n', "<br>")
+    };
+    let mut lines = Vec::with_capacity(table.len() + 1);
+    lines.push(format!(
+        "| {} |",
+        header.iter().map(escape).collect::<Vec<_>>().join(" | ")
+    ));
+    lines.push(format!("|{}", " --- |".repeat(header.len())));
+    for row in &table[1..] {
+        lines.push(format!(
+            "| {} |",
+            row.iter().map(escape).collect::<Vec<_>>().join(" | ")
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    #[test]
+    fn parses_csv_with_headers() {
+        let csv = b"\xEF\xBB\xBFname;price;notes\nWidget;9.99;a|b\n;;\nGadget;12;\n";
+        let workbook = parse_spreadsheet(csv, None, &Limits::default()).unwrap();
+        let sheet = &workbook.sheets[0];
+        assert_eq!(sheet.headers, ["name", "price", "notes"]);
+        assert_eq!(sheet.total_rows, 2);
+        assert_eq!(
+            Value::from(sheet.rows.clone()),
+            json!([
+                { "name": "Widget", "price": "9.99", "notes": "a|b" },
+                { "name": "Gadget", "price": "12", "notes": null },
+            ])
+        );
+        assert_eq!(
+            sheet.markdown,
+            "| name | price | notes |\n| --- | --- | --- |\n| Widget | 9.99 | a\\|b |\n| Gadget | 12 |  |"
+        );
+        assert!(!sheet.truncated);
+    }
+
+    #[test]
+    fn applies_limits() {
+        let csv = b"a,,a,d\n1,2,3,4\n5,6,7,8\nlonger,x,y,z\n";
+        let limits = Limits {
+            max_rows: 2,
+            max_columns: 3,
+            max_cell_chars: 3,
+            ..Limits::default()
+        };
+        let sheet = parse_spreadsheet(csv, Some(Format::Csv), &limits)
+            .unwrap()
+            .sheets
+            .remove(0);
+        assert_eq!(sheet.headers, ["a", "B", "a_2"]);
+        assert_eq!(sheet.rows.len(), 2);
+        assert_eq!(sheet.total_rows, 2);
+        assert!(sheet.truncated);
+    }
+
+    #[test]
+    fn caps_header_cells() {
+        let csv = b"a long header,b\n1,2\n";
+        let limits = Limits {
+            max_columns: usize::MAX,
+            max_cell_chars: 6,
+            ..Limits::default()
+        };
+        let sheet = parse_spreadsheet(csv, Some(Format::Csv), &limits)
+            .unwrap()
+            .sheets
+            .remove(0);
+        assert_eq!(sheet.headers, ["a long", "b"]);
+        assert!(sheet.truncated);
+    }
+
+    #[test]
+    fn rejects_workbooks_that_decompress_past_the_package_limits() {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        writer
+            .start_file("xl/worksheets/sheet1.xml", SimpleFileOptions::default())
+            .unwrap();
+        let zeros = vec![0u8; 1 << 20];
+        for _ in 0..=(zip_package::Limits::default().max_entry_bytes >> 20) {
+            writer.write_all(&zeros).unwrap();
+        }
+        let data = writer.finish().unwrap().into_inner();
+        assert!(matches!(
+            parse_spreadsheet(&data, None, &Limits::default()),
+            Err(SpreadsheetError::Package(
+                PackageError::EntryTooLarge { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn stops_reading_at_the_row_cap() {
+        let rows = (0..).map(|index| Ok(vec![Value::from(index)]));
+        let limits = Limits {
+            max_rows: 3,
+            ..Limits::default()
+        };
+        let sheet = build_sheet("Endless".to_string(), rows, &limits).unwrap();
+        assert_eq!(sheet.headers, ["0"]);
+        assert_eq!(sheet.total_rows, 3);
+        assert!(sheet.truncated);
+    }
+
+    #[test]
+    fn parses_xlsx_workbooks() {
+        let mut book = rust_xlsxwriter::Workbook::new();
+        let first = book.add_worksheet().set_name("Prices").unwrap();
+        first.write(0, 0, "item").unwrap();
+        first.write(0, 1, "price").unwrap();
+        first.write(1, 0, "Widget").unwrap();
+        first.write(1, 1, 9.5).unwrap();
+        first.write(2, 0, "Gadget").unwrap();
+        first.write(2, 1, true).unwrap();
+        book.add_worksheet().set_name("Empty").unwrap();
+        let data = book.save_to_buffer().unwrap();
+
+        let workbook = parse_spreadsheet(&data, None, &Limits::default()).unwrap();
+        assert_eq!(workbook.sheets.len(), 2);
+        let sheet = &workbook.sheets[0];
+        assert_eq!(sheet.name, "Prices");
+        assert_eq!(
+            Value::from(sheet.rows.clone()),
+            json!([{ "item": "Widget", "price": 9.5 }, { "item": "Gadget", "price": true }])
+        );
+        assert!(workbook.sheets[1].rows.is_empty());
+        assert_eq!(workbook.sheets[1].markdown, "");
+    }
+
+    #[test]
+    fn names_columns_like_spreadsheets() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(column_name(702), "AAA");
+    }
+}
@@ -0,0 +1,80 @@
+//! C ABI for the API. Results are JSON strings owned by Rust and must be
+//! handed back to [`text_free_string`].
+
+use std::ffi::{c_char, CStr};
+
+use ffi_support::{catch_panic, error_json, into_c_string};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{segment_text, truncate_sentences, Unit};
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Options {
+    unit: Unit,
+    /// BCP 47 tag; only the primary subtag is used.
+    language: Option<String>,
+}
+
+unsafe fn optional_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        None
+    } else {
+        CStr::from_ptr(ptr).to_str().ok()
+    }
+}
+
+/// Splits the NUL-terminated UTF-8 `text` and returns `{"segments": [...]}`,
+/// or `{"error"}`. `options` is an optional JSON object
+/// (`{"unit": "sentence" | "paragraph", "language": "de"}`).
+///
+/// # Safety
+/// `text` must be a valid NUL-terminated string and `options` null or one.
+/// The returned pointer must be released with [`text_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn text_segment(text: *const c_char, options: *const c_char) -> *mut c_char {
+    catch_panic(|| {
+        let Some(text) = optional_str(text) else {
+            return error_json("text is null or not valid UTF-8".to_string());
+        };
+        let options = match optional_str(options).map(serde_json::from_str::<Options>) {
+            None => Options::default(),
+            Some(Ok(options)) => options,
+            Some(Err(err)) => return error_json(format!("invalid options: {err}")),
+        };
+        let segments = segment_text(text, options.unit, options.language.as_deref());
+        into_c_string(json!({ "segments": segments }).to_string())
+    })
+}
+
+/// Returns `{"text"}` holding the whole sentences of `text` that fit in
+/// `max_chars` characters, or `{"error"}`. `language` may be null.
+///
+/// # Safety
+/// `text` must be a valid NUL-terminated string and `language` null or one.
+/// The returned pointer must be released with [`text_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn text_truncate(
+    text: *const c_char,
+    max_chars: usize,
+    language: *const c_char,
+) -> *mut c_char {
+    catch_panic(|| {
+        let Some(text) = optional_str(text) else {
+            return error_json("text is null or not valid UTF-8".to_string());
+        };
+        let truncated = truncate_sentences(text, max_chars, optional_str(language));
+        into_c_string(json!({ "text": truncated }).to_string())
+    })
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+/// `ptr` must come from a function of this library and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn text_free_string(ptr: *mut c_char) {
+    ffi_support::free_string(ptr);
+}
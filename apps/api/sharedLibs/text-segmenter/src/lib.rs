@@ -0,0 +1,281 @@
+//! Sentence and paragraph segmentation for summaries and llms.txt sections.
+//! Sentence boundaries come from Unicode text segmentation (UAX #29), which
+//! gets most punctuation and CJK right but splits after abbreviations and
+//! initials; a per-language abbreviation list repairs those.
+
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+mod ffi;
+#[cfg(feature = "napi")]
+pub mod node;
+
+pub use ffi::{text_free_string, text_segment, text_truncate};
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    #[default]
+    Sentence,
+    Paragraph,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Segment {
+    pub text: String,
+    /// UTF-16 offsets, so `text.slice(start, end)` in JS is the segment.
+    pub start: usize,
+    pub end: usize,
+    pub byte_start: usize,
+    pub byte_end: usize,
+}
+
+const ABBREVIATIONS_EN: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "etc", "e.g", "i.e", "inc", "ltd",
+    "co", "corp", "jan", "feb", "mar", "apr", "jun", "jul", "aug", "sep", "sept", "oct", "nov",
+    "dec", "no", "fig", "approx", "dept", "est",
+];
+const ABBREVIATIONS_DE: &[&str] = &[
+    "dr", "prof", "hr", "fr", "z.b", "bzw", "usw", "vgl", "ca", "nr", "str", "ggf", "inkl", "evtl",
+    "d.h", "u.a", "s",
+];
+const ABBREVIATIONS_FR: &[&str] = &[
+    "m", "mme", "mlle", "dr", "pr", "p.ex", "etc", "cf", "av", "bd", "env", "n°",
+];
+const ABBREVIATIONS_ES: &[&str] = &[
+    "sr", "sra", "srta", "dr", "dra", "ud", "uds", "etc", "p.ej", "núm", "av", "pág",
+];
+
+fn abbreviations(language: Option<&str>) -> &'static [&'static str] {
+    let primary = language
+        .and_then(|language| language.split(['-', '_']).next())
+        .map(str::to_ascii_lowercase);
+    match primary.as_deref() {
+        Some("de") => ABBREVIATIONS_DE,
+        Some("fr") => ABBREVIATIONS_FR,
+        Some("es") => ABBREVIATIONS_ES,
+        _ => ABBREVIATIONS_EN,
+    }
+}
+
+/// Whether `sentence` ends in an abbreviation or an initial rather than a
+/// full stop.
+fn ends_with_abbreviation(sentence: &str, abbreviations: &[&str]) -> bool {
+    let Some(word) = sentence.trim_end().strip_suffix('.') else {
+        return false;
+    };
+    let last = word
+        .rsplit(|ch: char| ch.is_whitespace() || ch == '(')
+        .next()
+        .unwrap_or(word);
+    let mut chars = last.chars();
+    let initial = matches!((chars.next(), chars.next()), (Some(ch), None) if ch.is_uppercase());
+    initial || abbreviations.contains(&last.to_lowercase().as_str())
+}
+
+pub fn split_sentences(text: &str, language: Option<&str>) -> Vec<Segment> {
+    let abbreviations = abbreviations(language);
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut pending: Option<usize> = None;
+    for (index, sentence) in text.split_sentence_bound_indices() {
+        let start = pending.take().unwrap_or(index);
+        let end = index + sentence.len();
+        if ends_with_abbreviation(&text[start..end], abbreviations) {
+            pending = Some(start);
+            continue;
+        }
+        ranges.push((start, end));
+    }
+    if let Some(start) = pending {
+        ranges.push((start, text.len()));
+    }
+    let mut offsets = Utf16Offsets::new(text);
+    ranges
+        .into_iter()
+        .filter_map(|(start, end)| segment(text, start, end, &mut offsets))
+        .collect()
+}
+
+/// Paragraphs are separated by one or more blank lines.
+pub fn split_paragraphs(text: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut offsets = Utf16Offsets::new(text);
+    let mut start = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if let Some(paragraph_start) = start.take() {
+                segments.extend(segment(text, paragraph_start, offset, &mut offsets));
+            }
+        } else if start.is_none() {
+            start = Some(offset);
+        }
+        offset += line.len();
+    }
+    if let Some(paragraph_start) = start {
+        segments.extend(segment(text, paragraph_start, text.len(), &mut offsets));
+    }
+    segments
+}
+
+pub fn segment_text(text: &str, unit: Unit, language: Option<&str>) -> Vec<Segment> {
+    match unit {
+        Unit::Sentence => split_sentences(text, language),
+        Unit::Paragraph => split_paragraphs(text),
+    }
+}
+
+/// The longest run of whole sentences that fits in `max_chars` characters.
+/// When even the first sentence is too long it is cut at a word boundary and
+/// ends with an ellipsis.
+pub fn truncate_sentences(text: &str, max_chars: usize, language: Option<&str>) -> String {
+    let sentences = split_sentences(text, language);
+    let mut end = None;
+    let mut chars = 0;
+    for sentence in &sentences {
+        let from = end.unwrap_or(sentence.byte_start);
+        chars += text[from..sentence.byte_end].chars().count();
+        if chars > max_chars {
+            break;
+        }
+        end = Some(sentence.byte_end);
+    }
+    match (sentences.first(), end) {
+        (Some(first), Some(end)) => text[first.byte_start..end].to_string(),
+        (Some(first), None) => {
+            let budget = max_chars.saturating_sub(1);
+            let cut: String = first.text.chars().take(budget).collect();
+            let cut = match cut.rfind(char::is_whitespace) {
+                Some(space) if space > 0 => &cut[..space],
+                _ => cut.as_str(),
+            };
+            if cut.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "{}…",
+                    cut.trim_end_matches(|ch: char| ch.is_whitespace() || ch == ',')
+                )
+            }
+        }
+        (None, _) => String::new(),
+    }
+}
+
+/// Converts byte offsets of `text` to UTF-16 offsets. Offsets are expected to
+/// grow, so each stretch of text is only counted once; going back restarts
+/// the count from the beginning.
+struct Utf16Offsets<'a> {
+    text: &'a str,
+    byte: usize,
+    utf16: usize,
+}
+
+impl<'a> Utf16Offsets<'a> {
+    fn new(text: &'a str) -> Self {
+        Utf16Offsets {
+            text,
+            byte: 0,
+            utf16: 0,
+        }
+    }
+
+    fn at(&mut self, byte: usize) -> usize {
+        if byte < self.byte {
+            (self.byte, self.utf16) = (0, 0);
+        }
+        self.utf16 += self.text[self.byte..byte].encode_utf16().count();
+        self.byte = byte;
+        self.utf16
+    }
+}
+
+/// Trims whitespace off `text[start..end]`; `None` when nothing is left.
+fn segment(text: &str, start: usize, end: usize, offsets: &mut Utf16Offsets) -> Option<Segment> {
+    let slice = &text[start..end];
+    let byte_start = start + (slice.len() - slice.trim_start().len());
+    let byte_end = start + slice.trim_end().len();
+    (byte_start < byte_end).then(|| Segment {
+        text: text[byte_start..byte_end].to_string(),
+        start: offsets.at(byte_start),
+        end: offsets.at(byte_end),
+        byte_start,
+        byte_end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sentences(text: &str, language: Option<&str>) -> Vec<String> {
+        split_sentences(text, language)
+            .into_iter()
+            .map(|segment| segment.text)
+            .collect()
+    }
+
+    #[test]
+    fn keeps_abbreviations_and_initials_inside_sentences() {
+        assert_eq!(
+            sentences(
+                "Dr. Smith met J. K. Rowling at 3.30 p.m. today. It went well!  Really?",
+                None
+            ),
+            [
+                "Dr. Smith met J. K. Rowling at 3.30 p.m. today.",
+                "It went well!",
+                "Really?"
+            ]
+        );
+        assert_eq!(
+            sentences("Das ist z.B. ein Test. Noch einer.", Some("de-DE")),
+            ["Das ist z.B. ein Test.", "Noch einer."]
+        );
+    }
+
+    #[test]
+    fn splits_cjk() {
+        assert_eq!(
+            sentences("今日は晴れ。明日は雨。", Some("ja")),
+            ["今日は晴れ。", "明日は雨。"]
+        );
+    }
+
+    #[test]
+    fn splits_paragraphs_with_offsets() {
+        let text = "First 😀 line\nsame paragraph\n\n\n  Second\n";
+        let paragraphs = split_paragraphs(text);
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(paragraphs[0].text, "First 😀 line\nsame paragraph");
+        assert_eq!(paragraphs[1].text, "Second");
+        assert_eq!(
+            &text[paragraphs[1].byte_start..paragraphs[1].byte_end],
+            "Second"
+        );
+        assert_eq!(paragraphs[1].start, paragraphs[1].byte_start - 2);
+    }
+
+    #[test]
+    fn reports_utf16_offsets() {
+        let text = "Ein 😀. Zwei 😀😀. Drei.";
+        let offsets: Vec<_> = split_sentences(text, None)
+            .into_iter()
+            .map(|segment| (segment.start, segment.end))
+            .collect();
+        assert_eq!(offsets, [(0, 7), (8, 18), (19, 24)]);
+    }
+
+    #[test]
+    fn truncates_at_sentence_boundaries() {
+        let text = "One short sentence. Another one here. And a third.";
+        assert_eq!(
+            truncate_sentences(text, 40, None),
+            "One short sentence. Another one here."
+        );
+        assert_eq!(truncate_sentences(text, 1000, None), text);
+        assert_eq!(truncate_sentences(text, 12, None), "One short…");
+        assert_eq!(truncate_sentences("", 10, None), "");
+    }
+}
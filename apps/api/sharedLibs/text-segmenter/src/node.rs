@@ -0,0 +1,24 @@
+//! Node-API bindings, built with `--features napi`.
+
+use napi_derive::napi;
+
+use crate::{split_paragraphs, split_sentences, truncate_sentences};
+
+/// Returns the sentences as a JSON array of segments.
+#[napi(js_name = "splitSentences")]
+pub fn sentences(text: String, language: Option<String>) -> napi::Result<String> {
+    serde_json::to_string(&split_sentences(&text, language.as_deref()))
+        .map_err(|err| napi::Error::from_reason(err.to_string()))
+}
+
+/// Returns the paragraphs as a JSON array of segments.
+#[napi(js_name = "splitParagraphs")]
+pub fn paragraphs(text: String) -> napi::Result<String> {
+    serde_json::to_string(&split_paragraphs(&text))
+        .map_err(|err| napi::Error::from_reason(err.to_string()))
+}
+
+#[napi(js_name = "truncateSentences")]
+pub fn truncate(text: String, max_chars: u32, language: Option<String>) -> String {
+    truncate_sentences(&text, max_chars as usize, language.as_deref())
+}
@@ -0,0 +1,129 @@
+//! C ABI for the API. Inputs and results are JSON strings; results are owned
+//! by Rust and must be handed back to [`warc_free_string`]. WARC bytes are base64
+//! encoded in the result.
+
+use std::collections::BTreeMap;
+use std::ffi::c_char;
+
+use base64::Engine;
+use ffi_support::{catch_panic, into_c_string};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{write_capture, write_warcinfo, Capture};
+
+#[derive(Deserialize)]
+struct WarcInfo {
+    filename: String,
+    #[serde(default)]
+    fields: BTreeMap<String, String>,
+}
+
+/// Writes the records for the [`crate::Capture`] JSON in `data[..len]` and
+/// returns `{"data", "responseId", "recordIds"}`, or `{"error"}`.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes. The returned pointer must be
+/// released with [`warc_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn warc_capture(data: *const u8, len: usize, gzip: bool) -> *mut c_char {
+    catch_panic(|| {
+        let result = serde_json::from_slice::<Capture>(input(data, len))
+            .map_err(|err| err.to_string())
+            .and_then(|capture| write_capture(&capture, gzip).map_err(|err| err.to_string()))
+            .map(|written| {
+                json!({
+                    "data": base64::engine::general_purpose::STANDARD.encode(written.data),
+                    "responseId": written.response_id,
+                    "recordIds": written.record_ids,
+                })
+            });
+        respond(result)
+    })
+}
+
+/// Writes the `warcinfo` record for `{"filename", "fields"}` in
+/// `data[..len]` and returns `{"data"}`, or `{"error"}`.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes. The returned pointer must be
+/// released with [`warc_free_string`].
+#[no_mangle]
+pub unsafe extern "C" fn warc_info(data: *const u8, len: usize, gzip: bool) -> *mut c_char {
+    catch_panic(|| {
+        let result = serde_json::from_slice::<WarcInfo>(input(data, len))
+            .map_err(|err| err.to_string())
+            .and_then(|info| {
+                write_warcinfo(&info.filename, &info.fields, gzip).map_err(|err| err.to_string())
+            })
+            .map(
+                |written| json!({ "data": base64::engine::general_purpose::STANDARD.encode(written) }),
+            );
+        respond(result)
+    })
+}
+
+unsafe fn input<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if data.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, len)
+    }
+}
+
+fn respond(result: Result<Value, String>) -> *mut c_char {
+    into_c_string(
+        result
+            .unwrap_or_else(|err| json!({ "error": err }))
+            .to_string(),
+    )
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+/// `ptr` must come from a function of this library and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn warc_free_string(ptr: *mut c_char) {
+    ffi_support::free_string(ptr);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    fn call(f: unsafe extern "C" fn(*const u8, usize, bool) -> *mut c_char, input: &str) -> Value {
+        unsafe {
+            let ptr = f(input.as_ptr(), input.len(), false);
+            let value = serde_json::from_str(CStr::from_ptr(ptr).to_str().unwrap()).unwrap();
+            warc_free_string(ptr);
+            value
+        }
+    }
+
+    #[test]
+    fn writes_capture_from_json() {
+        let value = call(
+            warc_capture,
+            r#"{"url":"https://example.com/","response":{"status":404,"headers":[["Content-Type","text/plain"]],"body":"bm9wZQ=="}}"#,
+        );
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(value["data"].as_str().unwrap())
+            .unwrap();
+        let text = String::from_utf8(data).unwrap();
+        assert!(text
+            .contains("HTTP/1.1 404\r\nContent-Type: text/plain\r\nContent-Length: 4\r\n\r\nnope"));
+        assert_eq!(value["recordIds"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn reports_errors_as_json() {
+        let value = call(warc_capture, r#"{"url":"https://example.com/"}"#);
+        assert!(value["error"].as_str().unwrap().contains("response"));
+        let value = call(warc_info, r#"{"filename":"a.warc"}"#);
+        assert!(value["data"].is_string());
+    }
+}
@@ -0,0 +1,471 @@
+//! Writes WARC/1.1 records (ISO 28500) for scraped pages so crawls can be
+//! exported to standard web-archive tooling (pywb, warcio, the Wayback
+//! Machine). Every page becomes a `response` record, optionally preceded by
+//! its `request` and followed by a `metadata` record, all linked through
+//! `WARC-Concurrent-To`.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use base64::Engine;
+use chrono::{DateTime, SecondsFormat, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Deserializer};
+use sha1::{Digest, Sha1};
+
+mod ffi;
+#[cfg(feature = "napi")]
+pub mod node;
+
+pub use ffi::{warc_capture, warc_free_string, warc_info};
+
+const WARC_VERSION: &str = "WARC/1.1";
+
+/// Hop-by-hop and encoding headers that no longer describe the stored body:
+/// the scraper hands us the decoded payload, so they're dropped and
+/// `Content-Length` is recomputed.
+const STRIPPED_HEADERS: [&str; 3] = ["content-encoding", "content-length", "transfer-encoding"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum WarcError {
+    #[error("invalid capture date {0:?}, expected RFC 3339")]
+    InvalidDate(String),
+    #[error("target uri must not be empty or contain whitespace: {0:?}")]
+    InvalidTargetUri(String),
+    /// A line break would end the header early and let the rest of the
+    /// value inject headers or records of its own.
+    #[error("header field must not contain CR or LF: {0:?}")]
+    LineBreak(String),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// One fetched page. Bodies are base64 in JSON.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capture {
+    pub url: String,
+    /// RFC 3339 fetch time, defaults to now.
+    pub date: Option<String>,
+    pub ip_address: Option<String>,
+    pub request: Option<HttpRequest>,
+    pub response: HttpResponse,
+    /// Written as `application/warc-fields`, e.g. the scrape id or the
+    /// engine that fetched the page.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpRequest {
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    #[serde(default, deserialize_with = "deserialize_base64")]
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpResponse {
+    pub status: u16,
+    pub status_text: Option<String>,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    #[serde(default, deserialize_with = "deserialize_base64")]
+    pub body: Vec<u8>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+fn deserialize_base64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let encoded = String::deserialize(deserializer)?;
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(serde::de::Error::custom)
+}
+
+/// Records produced for one capture, ready to be appended to a `.warc` or
+/// `.warc.gz` file.
+#[derive(Debug)]
+pub struct WrittenCapture {
+    pub data: Vec<u8>,
+    /// `WARC-Record-ID` of the response record, for CDX indexes.
+    pub response_id: String,
+    pub record_ids: Vec<String>,
+}
+
+struct Record<'a> {
+    kind: &'a str,
+    target_uri: Option<&'a str>,
+    date: &'a str,
+    content_type: &'a str,
+    extra: Vec<(&'static str, String)>,
+    block: Vec<u8>,
+}
+
+impl Record<'_> {
+    fn write(self, id: &str, gzip: bool, out: &mut Vec<u8>) -> Result<(), WarcError> {
+        for (_, value) in &self.extra {
+            single_line(value)?;
+        }
+        let mut header = format!(
+            "{WARC_VERSION}\r\nWARC-Type: {}\r\nWARC-Record-ID: {id}\r\nWARC-Date: {}\r\n",
+            self.kind, self.date
+        );
+        if let Some(uri) = self.target_uri {
+            header.push_str(&format!("WARC-Target-URI: {uri}\r\n"));
+        }
+        for (name, value) in &self.extra {
+            header.push_str(&format!("{name}: {value}\r\n"));
+        }
+        header.push_str(&format!(
+            "WARC-Block-Digest: {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+            sha1_digest(&self.block),
+            self.content_type,
+            self.block.len()
+        ));
+
+        // Each record is its own gzip member, as `.warc.gz` readers expect.
+        if gzip {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            write_record(&mut encoder, &header, &self.block)?;
+            out.extend(encoder.finish()?);
+        } else {
+            write_record(out, &header, &self.block)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_record(out: &mut impl Write, header: &str, block: &[u8]) -> std::io::Result<()> {
+    out.write_all(header.as_bytes())?;
+    out.write_all(block)?;
+    out.write_all(b"\r\n\r\n")
+}
+
+/// `sha1:` followed by the base32 digest, the form used by Heritrix and
+/// wget and understood by every CDX indexer.
+fn sha1_digest(data: &[u8]) -> String {
+    format!("sha1:{}", data_encoding::BASE32.encode(&Sha1::digest(data)))
+}
+
+fn record_id() -> String {
+    format!("<urn:uuid:{}>", uuid::Uuid::new_v4())
+}
+
+fn warc_date(date: Option<&str>) -> Result<String, WarcError> {
+    let date = match date {
+        Some(date) => DateTime::parse_from_rfc3339(date)
+            .map_err(|_| WarcError::InvalidDate(date.to_string()))?
+            .with_timezone(&Utc),
+        None => Utc::now(),
+    };
+    Ok(date.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+fn single_line(value: &str) -> Result<&str, WarcError> {
+    if value.contains(['\r', '\n']) {
+        return Err(WarcError::LineBreak(value.to_string()));
+    }
+    Ok(value)
+}
+
+fn http_head(
+    start_line: &str,
+    headers: &[(String, String)],
+    body_len: usize,
+) -> Result<Vec<u8>, WarcError> {
+    let mut head = format!("{}\r\n", single_line(start_line)?);
+    for (name, value) in headers {
+        if !STRIPPED_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+            head.push_str(&format!(
+                "{}: {}\r\n",
+                single_line(name)?,
+                single_line(value)?
+            ));
+        }
+    }
+    head.push_str(&format!("Content-Length: {body_len}\r\n\r\n"));
+    Ok(head.into_bytes())
+}
+
+/// Serializes a capture into its request, response and metadata records.
+pub fn write_capture(capture: &Capture, gzip: bool) -> Result<WrittenCapture, WarcError> {
+    let url = capture.url.as_str();
+    if url.is_empty() || url.contains(char::is_whitespace) {
+        return Err(WarcError::InvalidTargetUri(url.to_string()));
+    }
+    let date = warc_date(capture.date.as_deref())?;
+    let response_id = record_id();
+    let mut data = Vec::new();
+    let mut record_ids = Vec::new();
+
+    if let Some(request) = &capture.request {
+        let id = record_id();
+        let mut block = http_head(
+            &format!("{} {} HTTP/1.1", request.method, request_target(url)),
+            &request.headers,
+            request.body.len(),
+        )?;
+        block.extend_from_slice(&request.body);
+        Record {
+            kind: "request",
+            target_uri: Some(url),
+            date: &date,
+            content_type: "application/http; msgtype=request",
+            extra: vec![("WARC-Concurrent-To", response_id.clone())],
+            block,
+        }
+        .write(&id, gzip, &mut data)?;
+        record_ids.push(id);
+    }
+
+    let response = &capture.response;
+    let status_line = match &response.status_text {
+        Some(text) => format!("HTTP/1.1 {} {text}", response.status),
+        None => format!("HTTP/1.1 {}", response.status),
+    };
+    let mut block = http_head(&status_line, &response.headers, response.body.len())?;
+    block.extend_from_slice(&response.body);
+    let mut extra = vec![("WARC-Payload-Digest", sha1_digest(&response.body))];
+    if let Some(ip) = &capture.ip_address {
+        extra.push(("WARC-IP-Address", ip.clone()));
+    }
+    Record {
+        kind: "response",
+        target_uri: Some(url),
+        date: &date,
+        content_type: "application/http; msgtype=response",
+        extra,
+        block,
+    }
+    .write(&response_id, gzip, &mut data)?;
+    record_ids.push(response_id.clone());
+
+    if !capture.metadata.is_empty() {
+        let id = record_id();
+        Record {
+            kind: "metadata",
+            target_uri: Some(url),
+            date: &date,
+            content_type: "application/warc-fields",
+            extra: vec![("WARC-Concurrent-To", response_id.clone())],
+            block: warc_fields(&capture.metadata)?,
+        }
+        .write(&id, gzip, &mut data)?;
+        record_ids.push(id);
+    }
+
+    Ok(WrittenCapture {
+        data,
+        response_id,
+        record_ids,
+    })
+}
+
+/// The `warcinfo` record that opens a WARC file.
+pub fn write_warcinfo(
+    filename: &str,
+    fields: &BTreeMap<String, String>,
+    gzip: bool,
+) -> Result<Vec<u8>, WarcError> {
+    let date = warc_date(None)?;
+    let mut data = Vec::new();
+    Record {
+        kind: "warcinfo",
+        target_uri: None,
+        date: &date,
+        content_type: "application/warc-fields",
+        extra: vec![("WARC-Filename", filename.to_string())],
+        block: warc_fields(fields)?,
+    }
+    .write(&record_id(), gzip, &mut data)?;
+    Ok(data)
+}
+
+fn warc_fields(fields: &BTreeMap<String, String>) -> Result<Vec<u8>, WarcError> {
+    let mut block = String::new();
+    for (name, value) in fields {
+        let value = value.replace(['\r', '\n'], " ");
+        block.push_str(&format!("{}: {value}\r\n", single_line(name)?));
+    }
+    Ok(block.into_bytes())
+}
+
+/// Path and query of `url`, the request-target of an origin-form request.
+fn request_target(url: &str) -> &str {
+    let after_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let target = after_scheme
+        .find('/')
+        .map_or("/", |index| &after_scheme[index..]);
+    target.split('#').next().unwrap_or("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::MultiGzDecoder;
+
+    use super::*;
+
+    fn capture() -> Capture {
+        Capture {
+            url: "https://example.com/page?q=1#top".to_string(),
+            date: Some("2024-07-02T12:00:00.250+02:00".to_string()),
+            ip_address: Some("93.184.216.34".to_string()),
+            request: Some(HttpRequest {
+                method: "GET".to_string(),
+                headers: vec![("User-Agent".to_string(), "firecrawl".to_string())],
+                body: Vec::new(),
+            }),
+            response: HttpResponse {
+                status: 200,
+                status_text: Some("OK".to_string()),
+                headers: vec![
+                    ("Content-Type".to_string(), "text/html".to_string()),
+                    ("Content-Encoding".to_string(), "gzip".to_string()),
+                ],
+                body: b"<p>hello</p>".to_vec(),
+            },
+            metadata: BTreeMap::from([("scrapeId".to_string(), "abc".to_string())]),
+        }
+    }
+
+    fn header<'a>(record: &'a str, name: &str) -> Option<&'a str> {
+        record
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+    }
+
+    #[test]
+    fn writes_linked_records() {
+        let written = write_capture(&capture(), false).unwrap();
+        let text = String::from_utf8(written.data).unwrap();
+        let records: Vec<&str> = text
+            .split("WARC/1.1\r\n")
+            .filter(|record| !record.is_empty())
+            .collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(written.record_ids.len(), 3);
+        assert_eq!(written.record_ids[1], written.response_id);
+
+        let (request, response, metadata) = (records[0], records[1], records[2]);
+        assert_eq!(header(request, "WARC-Type"), Some("request"));
+        assert!(request.contains("GET /page?q=1 HTTP/1.1\r\n"));
+        assert_eq!(
+            header(request, "WARC-Concurrent-To"),
+            Some(written.response_id.as_str())
+        );
+
+        assert_eq!(header(response, "WARC-Date"), Some("2024-07-02T10:00:00Z"));
+        assert_eq!(
+            header(response, "WARC-Target-URI"),
+            Some("https://example.com/page?q=1#top")
+        );
+        assert_eq!(header(response, "WARC-IP-Address"), Some("93.184.216.34"));
+        assert_eq!(
+            header(response, "WARC-Payload-Digest"),
+            Some(sha1_digest(b"<p>hello</p>").as_str())
+        );
+        assert!(!response.contains("Content-Encoding"));
+        let block =
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 12\r\n\r\n<p>hello</p>";
+        assert!(response.contains(&format!(
+            "Content-Length: {}\r\n\r\n{block}\r\n\r\n",
+            block.len()
+        )));
+        assert_eq!(
+            header(response, "WARC-Block-Digest"),
+            Some(sha1_digest(block.as_bytes()).as_str())
+        );
+
+        assert_eq!(header(metadata, "WARC-Type"), Some("metadata"));
+        assert!(metadata.ends_with("scrapeId: abc\r\n\r\n\r\n"));
+    }
+
+    #[test]
+    fn gzips_each_record_separately() {
+        let plain = write_capture(&capture(), false).unwrap().data;
+        let gzipped = write_capture(&capture(), true).unwrap().data;
+        let members = gzipped
+            .windows(3)
+            .filter(|window| window == &[0x1f, 0x8b, 0x08])
+            .count();
+        assert_eq!(members, 3);
+
+        let mut decoded = String::new();
+        MultiGzDecoder::new(&gzipped[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded.len(), plain.len());
+        assert_eq!(decoded.matches("WARC/1.1\r\n").count(), 3);
+    }
+
+    #[test]
+    fn validates_input() {
+        let mut bad_date = capture();
+        bad_date.date = Some("yesterday".to_string());
+        assert!(matches!(
+            write_capture(&bad_date, false),
+            Err(WarcError::InvalidDate(_))
+        ));
+
+        let mut bad_url = capture();
+        bad_url.url = "https://example.com/a b".to_string();
+        assert!(matches!(
+            write_capture(&bad_url, false),
+            Err(WarcError::InvalidTargetUri(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_line_breaks_in_header_fields() {
+        let mut status = capture();
+        status.response.status_text = Some("OK\r\nSet-Cookie: a=b".to_string());
+        let mut ip = capture();
+        ip.ip_address = Some("1.2.3.4\r\n\r\nWARC/1.1".to_string());
+        let mut name = capture();
+        name.response
+            .headers
+            .push(("X-A\nX-B".to_string(), "c".to_string()));
+        let mut value = capture();
+        value.request.as_mut().unwrap().headers[0].1 = "firecrawl\n".to_string();
+        let mut method = capture();
+        method.request.as_mut().unwrap().method = "GET /\r\nHost: evil".to_string();
+        let mut field = capture();
+        field.metadata.insert("a\rb".to_string(), "c".to_string());
+        for capture in [status, ip, name, value, method, field] {
+            assert!(matches!(
+                write_capture(&capture, false),
+                Err(WarcError::LineBreak(_))
+            ));
+        }
+        assert!(matches!(
+            write_warcinfo("crawl.warc\r\nX: y", &BTreeMap::new(), false),
+            Err(WarcError::LineBreak(_))
+        ));
+    }
+
+    #[test]
+    fn writes_warcinfo() {
+        let fields = BTreeMap::from([("software".to_string(), "firecrawl".to_string())]);
+        let text =
+            String::from_utf8(write_warcinfo("crawl.warc", &fields, false).unwrap()).unwrap();
+        assert!(text.starts_with("WARC/1.1\r\nWARC-Type: warcinfo\r\n"));
+        assert_eq!(header(&text, "WARC-Filename"), Some("crawl.warc"));
+        assert!(text.ends_with("software: firecrawl\r\n\r\n\r\n"));
+    }
+
+    #[test]
+    fn extracts_request_target() {
+        assert_eq!(request_target("https://example.com"), "/");
+        assert_eq!(request_target("https://example.com/a/b?c=d#e"), "/a/b?c=d");
+    }
+}
@@ -0,0 +1,269 @@
+//! Bounded reads from zip based document packages (.docx, .pptx, .epub,
+//! .xlsx), plus the path and content type helpers the parsers share. Entry sizes in
+//! the zip headers are attacker controlled, so decompression is capped per
+//! entry and per package instead of trusting them.
+
+use std::io::{self, Cursor, Read};
+
+use base64::Engine;
+use serde::Serializer;
+use zip::ZipArchive;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PackageError {
+    #[error(transparent)]
+    Archive(#[from] zip::result::ZipError),
+    #[error("failed to read {part}: {source}")]
+    Io {
+        part: String,
+        source: std::io::Error,
+    },
+    #[error("{part} decompresses to more than {limit} bytes")]
+    EntryTooLarge { part: String, limit: u64 },
+    #[error("package decompresses to more than {limit} bytes")]
+    TooLarge { limit: u64 },
+}
+
+/// Decompressed byte budgets.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub max_entry_bytes: u64,
+    pub max_total_bytes: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_entry_bytes: 64 << 20,
+            max_total_bytes: 256 << 20,
+        }
+    }
+}
+
+pub struct Package<'a> {
+    archive: ZipArchive<Cursor<&'a [u8]>>,
+    limits: Limits,
+    total: u64,
+}
+
+impl<'a> Package<'a> {
+    pub fn open(data: &'a [u8]) -> Result<Self, PackageError> {
+        Self::with_limits(data, Limits::default())
+    }
+
+    pub fn with_limits(data: &'a [u8], limits: Limits) -> Result<Self, PackageError> {
+        Ok(Package {
+            archive: ZipArchive::new(Cursor::new(data))?,
+            limits,
+            total: 0,
+        })
+    }
+
+    /// Reads an entry, `None` if the package doesn't contain it.
+    pub fn read_bytes(&mut self, name: &str) -> Result<Option<Vec<u8>>, PackageError> {
+        let limit = self.entry_limit();
+        let file = match self.archive.by_name(name) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        // One byte past the limit tells a full entry apart from a truncated one
+        let mut buf = Vec::with_capacity(file.size().min(limit) as usize);
+        file.take(limit + 1)
+            .read_to_end(&mut buf)
+            .map_err(|source| PackageError::Io {
+                part: name.to_string(),
+                source,
+            })?;
+        self.record(name, buf.len() as u64, limit)?;
+        Ok(Some(buf))
+    }
+
+    /// Decompresses every entry without keeping it, failing the way
+    /// [`Package::read_bytes`] does once the limits are exceeded. For
+    /// packages that are then handed to a reader outside this crate.
+    pub fn check_limits(&mut self) -> Result<(), PackageError> {
+        for index in 0..self.archive.len() {
+            let limit = self.entry_limit();
+            let mut file = self.archive.by_index(index)?;
+            let name = file.name().to_string();
+            let read = io::copy(&mut file.by_ref().take(limit + 1), &mut io::sink()).map_err(
+                |source| PackageError::Io {
+                    part: name.clone(),
+                    source,
+                },
+            )?;
+            drop(file);
+            self.record(&name, read, limit)?;
+        }
+        Ok(())
+    }
+
+    fn entry_limit(&self) -> u64 {
+        let remaining = self.limits.max_total_bytes.saturating_sub(self.total);
+        self.limits.max_entry_bytes.min(remaining)
+    }
+
+    fn record(&mut self, name: &str, read: u64, limit: u64) -> Result<(), PackageError> {
+        if read > limit {
+            return Err(if limit < self.limits.max_entry_bytes {
+                PackageError::TooLarge {
+                    limit: self.limits.max_total_bytes,
+                }
+            } else {
+                PackageError::EntryTooLarge {
+                    part: name.to_string(),
+                    limit,
+                }
+            });
+        }
+        self.total += read;
+        Ok(())
+    }
+
+    pub fn read_string(&mut self, name: &str) -> Result<Option<String>, PackageError> {
+        Ok(self
+            .read_bytes(name)?
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+    }
+}
+
+/// Joins a relationship target with the directory of the source part,
+/// collapsing `..` segments the way package readers do.
+pub fn resolve_part_path(directory: &str, target: &str) -> String {
+    let mut segments: Vec<&str> = if target.starts_with('/') {
+        Vec::new()
+    } else {
+        directory.split('/').filter(|s| !s.is_empty()).collect()
+    };
+    for segment in target.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+pub fn image_content_type(name: &str) -> &'static str {
+    let extension = name.rsplit('.').next().unwrap_or_default();
+    match extension.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "emf" => "image/emf",
+        "wmf" => "image/wmf",
+        _ => "application/octet-stream",
+    }
+}
+
+/// `serialize_with` helper for binary payloads in the JSON output.
+pub fn serialize_base64<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn build_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn reads_entries() {
+        let data = build_zip(&[("a.xml", b"<a/>")]);
+        let mut package = Package::open(&data).unwrap();
+        assert_eq!(
+            package.read_string("a.xml").unwrap().as_deref(),
+            Some("<a/>")
+        );
+        assert!(package.read_bytes("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn caps_decompressed_bytes() {
+        let big = vec![0u8; 4096];
+        let data = build_zip(&[
+            ("big.bin", &big),
+            ("a.bin", &big[..600]),
+            ("b.bin", &big[..600]),
+        ]);
+        let limits = Limits {
+            max_entry_bytes: 1024,
+            max_total_bytes: 1500,
+        };
+
+        let mut package = Package::with_limits(&data, limits).unwrap();
+        assert!(matches!(
+            package.read_bytes("big.bin"),
+            Err(PackageError::EntryTooLarge { limit: 1024, .. })
+        ));
+        assert_eq!(package.read_bytes("a.bin").unwrap().unwrap().len(), 600);
+        assert_eq!(package.read_bytes("b.bin").unwrap().unwrap().len(), 600);
+        assert!(matches!(
+            package.read_bytes("a.bin"),
+            Err(PackageError::TooLarge { limit: 1500 })
+        ));
+    }
+
+    #[test]
+    fn checks_every_entry_against_the_limits() {
+        let big = vec![0u8; 4096];
+        let limits = Limits {
+            max_entry_bytes: 1024,
+            max_total_bytes: 1500,
+        };
+
+        let data = build_zip(&[("a.bin", &big[..600]), ("b.bin", &big[..600])]);
+        assert!(Package::with_limits(&data, limits)
+            .unwrap()
+            .check_limits()
+            .is_ok());
+
+        let data = build_zip(&[("big.bin", &big), ("a.bin", &big[..600])]);
+        assert!(matches!(
+            Package::with_limits(&data, limits).unwrap().check_limits(),
+            Err(PackageError::EntryTooLarge { limit: 1024, .. })
+        ));
+
+        let data = build_zip(&[
+            ("a.bin", &big[..600]),
+            ("b.bin", &big[..600]),
+            ("c.bin", &big[..600]),
+        ]);
+        assert!(matches!(
+            Package::with_limits(&data, limits).unwrap().check_limits(),
+            Err(PackageError::TooLarge { limit: 1500 })
+        ));
+    }
+
+    #[test]
+    fn resolves_relative_targets() {
+        assert_eq!(
+            resolve_part_path("word/", "media/a.png"),
+            "word/media/a.png"
+        );
+        assert_eq!(resolve_part_path("word/", "../media/a.png"), "media/a.png");
+        assert_eq!(
+            resolve_part_path("word/", "/word/media/a.png"),
+            "word/media/a.png"
+        );
+    }
+}
@@ -19,7 +19,7 @@ export async function crawlStatusController(req: Request, res: Response) {
       return res.status(404).json({ error: "Job not found" });
     }
 
-    const { current, current_url, total, current_step, partialDocs } = await job.progress();
+    const { current, current_url, total, current_step, partialDocs, dedup, warcPath } = await job.progress();
     res.json({
       status: await job.getState(),
       // progress: job.progress(),
@@ -29,6 +29,8 @@ export async function crawlStatusController(req: Request, res: Response) {
       total: total,
       data: job.returnvalue,
       partial_data: partialDocs ?? [],
+      dedup: dedup,
+      warc_path: warcPath,
     });
   } catch (error) {
     console.error(error);
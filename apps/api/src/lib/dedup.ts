@@ -0,0 +1,42 @@
+import { Document } from "./entities";
+import { loadNativeLibrary } from "./native";
+
+type DedupIndex = {
+  addDocument(id: string, text: string): string;
+  stats(): string;
+};
+
+type DedupLibrary = {
+  DedupIndex: new (options?: string) => DedupIndex;
+};
+
+export type DedupStats = {
+  documents: number;
+  duplicates: number;
+};
+
+/**
+ * Recognizes pages that nearly duplicate an earlier page of the same crawl
+ * (print views, tag archives, paginated copies) with the dedup-index native
+ * library. Pages are checked as they're scraped, so a duplicate has still
+ * been fetched but goes no further.
+ */
+export class NearDuplicateFilter {
+  private constructor(private readonly index: DedupIndex) {}
+
+  /** Returns null when the native library isn't built. */
+  static create(): NearDuplicateFilter | null {
+    const library = loadNativeLibrary<DedupLibrary>("dedup-index");
+    return library ? new NearDuplicateFilter(new library.DedupIndex()) : null;
+  }
+
+  /** Adds the page to the index and tells whether it matched an earlier one. */
+  isDuplicate(doc: Document): boolean {
+    const matches = JSON.parse(this.index.addDocument(doc.metadata?.sourceURL ?? "", doc.content ?? ""));
+    return matches.length > 0;
+  }
+
+  stats(): DedupStats {
+    return JSON.parse(this.index.stats());
+  }
+}
@@ -8,6 +8,8 @@ export interface Progress {
   };
   currentDocumentUrl?: string;
   currentDocument?: Document;
+  warcPath?: string;
+  dedup?: { documents: number; duplicates: number };
 }
 
 export type PageOptions = {
@@ -23,6 +25,15 @@ export type PageOptions = {
   parsePDF?: boolean;
   removeTags?: string | string[];
   onlyIncludeTags?: string | string[];
+  postprocessMarkdown?: boolean | MarkdownPostprocessOptions;
+};
+
+export type MarkdownPostprocessOptions = {
+  removeEmptyHeadings?: boolean;
+  collapseBlankLines?: boolean;
+  dedupeLinkRuns?: boolean;
+  minLinkRun?: number;
+  normalizeHeadings?: boolean;
 };
 
 export type ExtractorOptions = {
@@ -52,6 +63,8 @@ export type CrawlerOptions = {
   ignoreSitemap?: boolean;
   mode?: "default" | "fast"; // have a mode of some sort
   allowBackwardCrawling?: boolean;
+  warc?: boolean; // write the crawled pages to <WARC_OUTPUT_DIR>/<jobId>.warc.gz on the worker, reported as warc_path in the crawl status
+  deduplicate?: boolean; // drop pages that nearly duplicate an earlier page of the crawl as they're scraped; they're still fetched
 }
 
 export type WebScraperOptions = {
@@ -62,6 +75,7 @@ export type WebScraperOptions = {
   extractorOptions?: ExtractorOptions;
   concurrentRequests?: number;
   bullJobId?: string;
+  onRawHtml?: (sourceURL: string, rawHtml: string) => void; // fetched HTML of each scraped page, without adding it to the documents
 };
 
 export interface DocumentUrl {
@@ -126,5 +140,6 @@ export interface FireEngineResponse {
   screenshot: string;
   pageStatusCode?: number;
   pageError?: string;
+  unrecoverablePages?: number[];
 }
 
@@ -1,5 +1,16 @@
+import { MarkdownPostprocessOptions } from "./entities";
+import { loadNativeLibrary } from "./native";
 
-export function parseMarkdown(html: string) {
+type MarkdownPostprocessor = {
+  postprocessMarkdown(markdown: string, options?: string): string;
+};
+
+/**
+ * `postprocess` runs the markdown-postprocessor cleanup passes on the result:
+ * `true` for the defaults, or an object to toggle individual passes. It's
+ * skipped when the native library isn't built.
+ */
+export function parseMarkdown(html: string, postprocess?: boolean | MarkdownPostprocessOptions) {
   var TurndownService = require("turndown");
   var turndownPluginGfm = require('joplin-turndown-plugin-gfm')
 
@@ -50,5 +61,19 @@ export function parseMarkdown(html: string) {
     /\[Skip to Content\]\(#[^\)]*\)/gi,
     ""
   );
+
+  if (postprocess) {
+    const postprocessor = loadNativeLibrary<MarkdownPostprocessor>("markdown-postprocessor");
+    if (postprocessor) {
+      try {
+        markdownContent = postprocessor.postprocessMarkdown(
+          markdownContent,
+          postprocess === true ? undefined : JSON.stringify(postprocess)
+        );
+      } catch (error) {
+        console.error(`Failed to postprocess markdown -> ${error}`);
+      }
+    }
+  }
   return markdownContent;
 }
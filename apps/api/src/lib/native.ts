@@ -0,0 +1,37 @@
+import fs from "fs";
+import path from "path";
+
+const loaded = new Map<string, any>();
+
+/**
+ * Loads the Node-API build of one of the Rust crates in `sharedLibs`
+ * (`cargo build --release --features napi -p <crate>`). Returns null when the
+ * library hasn't been built, so callers can skip the native step.
+ */
+export function loadNativeLibrary<T>(crate: string): T | null {
+  if (loaded.has(crate)) {
+    return loaded.get(crate);
+  }
+
+  const directory =
+    process.env.SHARED_LIBS_DIR ??
+    path.join(process.cwd(), "sharedLibs", "target", "release");
+  const fileName =
+    process.platform === "win32"
+      ? `${crate.replace(/-/g, "_")}.dll`
+      : `lib${crate.replace(/-/g, "_")}.${process.platform === "darwin" ? "dylib" : "so"}`;
+  const filePath = path.join(directory, fileName);
+
+  let library: T | null = null;
+  if (fs.existsSync(filePath)) {
+    try {
+      const module = { exports: {} };
+      process.dlopen(module, filePath);
+      library = module.exports as T;
+    } catch (error) {
+      console.error(`Failed to load native library ${filePath} -> ${error}`);
+    }
+  }
+  loaded.set(crate, library);
+  return library;
+}
@@ -0,0 +1,54 @@
+import fs from "fs";
+import os from "os";
+import path from "path";
+import { Document } from "./entities";
+import { loadNativeLibrary } from "./native";
+
+type WarcWriter = {
+  warcCapture(capture: string, gzip?: boolean): Buffer;
+  warcInfo(filename: string, fields?: string, gzip?: boolean): Buffer;
+};
+
+/**
+ * Writes the pages of a crawl job to `<WARC_OUTPUT_DIR>/<jobId>.warc.gz` with
+ * the warc-writer native library and returns the file path. `rawHtml` maps a
+ * page's sourceURL to the HTML the scraper fetched; pages without it fall
+ * back to their html or content. The file stays on the local disk, so
+ * WARC_OUTPUT_DIR should point at shared storage to collect it from
+ * elsewhere. Throws when the library isn't built.
+ */
+export async function writeCrawlWarc(jobId: string, docs: Document[], rawHtml: Map<string, string>): Promise<string> {
+  const writer = loadNativeLibrary<WarcWriter>("warc-writer");
+  if (!writer) {
+    throw new Error("warc-writer native library is not built");
+  }
+
+  const filename = `${jobId}.warc.gz`;
+  const directory = process.env.WARC_OUTPUT_DIR ?? path.join(os.tmpdir(), "warc");
+  const records: Buffer[] = [
+    writer.warcInfo(filename, JSON.stringify({ software: "firecrawl", jobId }), true),
+  ];
+
+  for (const doc of docs) {
+    const url = doc.metadata?.sourceURL;
+    if (!url) continue;
+    const html = rawHtml.get(url) || doc.rawHtml || doc.html;
+    const body = html || doc.content || "";
+    const capture = {
+      url,
+      date: doc.createdAt ? new Date(doc.createdAt).toISOString() : undefined,
+      response: {
+        status: doc.metadata.pageStatusCode ?? 200,
+        headers: [["Content-Type", html ? "text/html; charset=utf-8" : "text/plain; charset=utf-8"]],
+        body: Buffer.from(body).toString("base64"),
+      },
+      metadata: { jobId },
+    };
+    records.push(writer.warcCapture(JSON.stringify(capture), true));
+  }
+
+  await fs.promises.mkdir(directory, { recursive: true });
+  const filePath = path.join(directory, filename);
+  await fs.promises.writeFile(filePath, Buffer.concat(records));
+  return filePath;
+}
@@ -4,6 +4,8 @@ import { WebScraperDataProvider } from "../scraper/WebScraper";
 import { DocumentUrl, Progress } from "../lib/entities";
 import { billTeam } from "../services/billing/credit_billing";
 import { Document } from "../lib/entities";
+import { writeCrawlWarc } from "../lib/warc";
+import { logtail } from "../services/logtail";
 
 export async function startWebScraperPipeline({
   job,
@@ -23,6 +25,10 @@ export async function startWebScraperPipeline({
           partialDocs = partialDocs.slice(-50);
         }
         job.progress({ ...progress, partialDocs: partialDocs });
+      } else if (progress.dedup) {
+        job.progress({ ...job.progress(), dedup: progress.dedup });
+      } else if (progress.warcPath) {
+        job.progress({ ...job.progress(), warcPath: progress.warcPath });
       }
     },
     onSuccess: (result) => {
@@ -48,6 +54,8 @@ export async function runWebScraper({
 }: RunWebScraperParams): Promise<RunWebScraperResult> {
   try {
     const provider = new WebScraperDataProvider();
+    // The archive keeps the fetched HTML, collected apart from the documents
+    const warcHtml = mode === "crawl" && crawlerOptions?.warc === true ? new Map<string, string>() : undefined;
     if (mode === "crawl") {
       await provider.setOptions({
         mode: mode,
@@ -55,6 +63,7 @@ export async function runWebScraper({
         crawlerOptions: crawlerOptions,
         pageOptions: pageOptions,
         bullJobId: bull_job_id,
+        onRawHtml: warcHtml ? (sourceURL, rawHtml) => warcHtml.set(sourceURL, rawHtml) : undefined,
       });
     } else {
       await provider.setOptions({
@@ -68,6 +77,16 @@ export async function runWebScraper({
       inProgress(progress);
     })) as Document[];
 
+    if (warcHtml && docs.length > 0) {
+      try {
+        const warcPath = await writeCrawlWarc(bull_job_id, docs, warcHtml);
+        logtail.info("Wrote crawl WARC", { job_id: bull_job_id, path: warcPath });
+        inProgress({ warcPath });
+      } catch (error) {
+        logtail.error("Failed to write crawl WARC", { job_id: bull_job_id, error: error.message });
+      }
+    }
+
     if (docs.length === 0) {
       return {
         success: true,
@@ -18,6 +18,8 @@ import {
 import { generateCompletions } from "../../lib/LLM-extraction";
 import { getWebScraperQueue } from "../../../src/services/queue-service";
 import { fetchAndProcessDocx } from "./utils/docxProcessor";
+import { fetchAndProcessMhtml, isMhtmlUrl } from "./utils/mhtmlProcessor";
+import { NearDuplicateFilter } from "../../lib/dedup";
 import { getAdjustedMaxDepth, getURLDepth } from "./utils/maxDepthUtils";
 
 export class WebScraperDataProvider {
@@ -40,6 +42,8 @@ export class WebScraperDataProvider {
     "gpt-4-turbo";
   private crawlerMode: string = "default";
   private allowBackwardCrawling: boolean = false;
+  private onRawHtml?: (sourceURL: string, rawHtml: string) => void;
+  private dedupFilter: NearDuplicateFilter | null = null;
 
   authorize(): void {
     throw new Error("Method not implemented.");
@@ -65,11 +69,28 @@ export class WebScraperDataProvider {
           const existingHTML = allHtmls ? allHtmls[i + index] : "";
           const result = await scrapSingleUrl(
             url,
-            this.pageOptions,
+            this.onRawHtml ? { ...this.pageOptions, includeRawHtml: true } : this.pageOptions,
             this.extractorOptions,
             existingHTML
           );
+          if (this.onRawHtml) {
+            this.onRawHtml(result.metadata.sourceURL, result.rawHtml ?? "");
+            // Only requested for onRawHtml, so it stays out of progress, cache and results
+            if (!this.pageOptions.includeRawHtml && this.extractorOptions.mode !== "llm-extraction-from-raw-html") {
+              delete result.rawHtml;
+            }
+          }
           processedUrls++;
+          if (this.dedupFilter?.isDuplicate(result)) {
+            inProgress?.({
+              current: processedUrls,
+              total: totalUrls,
+              status: "SCRAPING",
+              currentDocumentUrl: url,
+              dedup: this.dedupFilter.stats(),
+            });
+            return;
+          }
           if (inProgress) {
             inProgress({
               current: processedUrls,
@@ -77,6 +98,7 @@ export class WebScraperDataProvider {
               status: "SCRAPING",
               currentDocumentUrl: url,
               currentDocument: { ...result, index: processedUrls },
+              dedup: this.dedupFilter?.stats(),
             });
           }
 
@@ -253,11 +275,16 @@ export class WebScraperDataProvider {
   ): Promise<Document[]> {
     const pdfLinks = links.filter(link => link.endsWith(".pdf"));
     const docLinks = links.filter(link => link.endsWith(".doc") || link.endsWith(".docx"));
+    const mhtmlLinks = links.filter(link => isMhtmlUrl(link));
 
-    const pdfDocuments = await this.fetchPdfDocuments(pdfLinks);
-    const docxDocuments = await this.fetchDocxDocuments(docLinks);
+    // Files are checked for near-duplicates first, pages as they're scraped
+    const fileDocuments = [
+      ...(await this.fetchPdfDocuments(pdfLinks)),
+      ...(await this.fetchDocxDocuments(docLinks)),
+      ...(await this.fetchMhtmlDocuments(mhtmlLinks)),
+    ].filter((document) => !this.dedupFilter?.isDuplicate(document));
 
-    links = links.filter(link => !pdfLinks.includes(link) && !docLinks.includes(link));
+    links = links.filter(link => !pdfLinks.includes(link) && !docLinks.includes(link) && !mhtmlLinks.includes(link));
 
     let documents = await this.convertUrlsToDocuments(
       links,
@@ -281,16 +308,16 @@ export class WebScraperDataProvider {
     ) {
       documents = await generateCompletions(documents, this.extractorOptions, "raw-html");
     }
-    return documents.concat(pdfDocuments).concat(docxDocuments);
+    return documents.concat(fileDocuments);
   }
 
   private async fetchPdfDocuments(pdfLinks: string[]): Promise<Document[]> {
     return Promise.all(
       pdfLinks.map(async (pdfLink) => {
-        const { content, pageStatusCode, pageError } = await fetchAndProcessPdf(pdfLink, this.pageOptions.parsePDF);
+        const { content, pageStatusCode, pageError, unrecoverablePages } = await fetchAndProcessPdf(pdfLink, this.pageOptions.parsePDF);
         return {
           content: content,
-          metadata: { sourceURL: pdfLink, pageStatusCode, pageError },
+          metadata: { sourceURL: pdfLink, pageStatusCode, pageError, unrecoverablePages },
           provider: "web-scraper",
         };
       })
@@ -309,6 +336,29 @@ export class WebScraperDataProvider {
     );
   }
 
+  private async fetchMhtmlDocuments(mhtmlLinks: string[]): Promise<Document[]> {
+    return Promise.all(
+      mhtmlLinks.map(async (link) => {
+        try {
+          const { content, html, originalUrl, pageStatusCode, pageError } = await fetchAndProcessMhtml(link, this.pageOptions?.postprocessMarkdown);
+          return {
+            content,
+            html: this.pageOptions?.includeHtml ? html : undefined,
+            metadata: { sourceURL: link, originalUrl, pageStatusCode, pageError },
+            provider: "web-scraper",
+          };
+        } catch (error) {
+          console.error(`Error processing mhtml archive ${link} -> ${error}`);
+          return {
+            content: "",
+            metadata: { sourceURL: link, pageError: error.message },
+            provider: "web-scraper",
+          };
+        }
+      })
+    );
+  }
+
   private applyPathReplacements(documents: Document[]): Document[] {
     if (this.replaceAllPathsWithAbsolutePaths) {
       documents = replacePathsWithAbsolutePaths(documents);
@@ -496,6 +546,9 @@ export class WebScraperDataProvider {
     this.crawlerMode = options.crawlerOptions?.mode ?? "default";
     this.ignoreSitemap = options.crawlerOptions?.ignoreSitemap ?? false;
     this.allowBackwardCrawling = options.crawlerOptions?.allowBackwardCrawling ?? false;
+    this.onRawHtml = options.onRawHtml;
+    this.dedupFilter =
+      this.mode === "crawl" && options.crawlerOptions?.deduplicate === true ? NearDuplicateFilter.create() : null;
 
     // make sure all urls start with https://
     this.urls = this.urls.map((url) => {
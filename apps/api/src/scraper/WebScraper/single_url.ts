@@ -100,11 +100,11 @@ export async function scrapWithFireEngine({
 
     const contentType = response.headers["content-type"];
     if (contentType && contentType.includes("application/pdf")) {
-      const { content, pageStatusCode, pageError } = await fetchAndProcessPdf(
+      const { content, pageStatusCode, pageError, unrecoverablePages } = await fetchAndProcessPdf(
         url,
         pageOptions?.parsePDF
       );
-      return { html: content, screenshot: "", pageStatusCode, pageError };
+      return { html: content, screenshot: "", pageStatusCode, pageError, unrecoverablePages };
     } else {
       const data = response.data;
       const html = data.content;
@@ -131,7 +131,7 @@ export async function scrapWithScrapingBee(
   wait_browser: string = "domcontentloaded",
   timeout: number = universalTimeout,
   pageOptions: { parsePDF?: boolean } = { parsePDF: true }
-): Promise<{ content: string; pageStatusCode?: number; pageError?: string }> {
+): Promise<{ content: string; pageStatusCode?: number; pageError?: string; unrecoverablePages?: number[] }> {
   try {
     const client = new ScrapingBeeClient(process.env.SCRAPING_BEE_API_KEY);
     const clientParams = await generateRequestParams(
@@ -181,7 +181,7 @@ export async function scrapWithPlaywright(
   waitFor: number = 0,
   headers?: Record<string, string>,
   pageOptions: { parsePDF?: boolean } = { parsePDF: true }
-): Promise<{ content: string; pageStatusCode?: number; pageError?: string }> {
+): Promise<{ content: string; pageStatusCode?: number; pageError?: string; unrecoverablePages?: number[] }> {
   try {
     const reqParams = await generateRequestParams(url);
     // If the user has passed a wait parameter in the request, use that
@@ -247,7 +247,7 @@ export async function scrapWithPlaywright(
 export async function scrapWithFetch(
   url: string,
   pageOptions: { parsePDF?: boolean } = { parsePDF: true }
-): Promise<{ content: string; pageStatusCode?: number; pageError?: string }> {
+): Promise<{ content: string; pageStatusCode?: number; pageError?: string; unrecoverablePages?: number[] }> {
   try {
     const response = await axios.get(url, {
       headers: {
@@ -367,7 +367,7 @@ export async function scrapSingleUrl(
     let scraperResponse: {
       text: string;
       screenshot: string;
-      metadata: { pageStatusCode?: number; pageError?: string | null };
+      metadata: { pageStatusCode?: number; pageError?: string | null; unrecoverablePages?: number[] };
     } = { text: "", screenshot: "", metadata: {} };
     let screenshot = "";
     switch (method) {
@@ -385,6 +385,7 @@ export async function scrapSingleUrl(
           scraperResponse.screenshot = response.screenshot;
           scraperResponse.metadata.pageStatusCode = response.pageStatusCode;
           scraperResponse.metadata.pageError = response.pageError;
+          scraperResponse.metadata.unrecoverablePages = response.unrecoverablePages;
         }
         break;
       case "scrapingBee":
@@ -397,6 +398,7 @@ export async function scrapSingleUrl(
           scraperResponse.text = response.content;
           scraperResponse.metadata.pageStatusCode = response.pageStatusCode;
           scraperResponse.metadata.pageError = response.pageError;
+          scraperResponse.metadata.unrecoverablePages = response.unrecoverablePages;
         }
         break;
       case "playwright":
@@ -409,6 +411,7 @@ export async function scrapSingleUrl(
           scraperResponse.text = response.content;
           scraperResponse.metadata.pageStatusCode = response.pageStatusCode;
           scraperResponse.metadata.pageError = response.pageError;
+          scraperResponse.metadata.unrecoverablePages = response.unrecoverablePages;
         }
         break;
       case "scrapingBeeLoad":
@@ -417,6 +420,7 @@ export async function scrapSingleUrl(
           scraperResponse.text = response.content;
           scraperResponse.metadata.pageStatusCode = response.pageStatusCode;
           scraperResponse.metadata.pageError = response.pageError;
+          scraperResponse.metadata.unrecoverablePages = response.unrecoverablePages;
         }
         break;
       case "fetch":
@@ -424,6 +428,7 @@ export async function scrapSingleUrl(
         scraperResponse.text = response.content;
         scraperResponse.metadata.pageStatusCode = response.pageStatusCode;
         scraperResponse.metadata.pageError = response.pageError;
+        scraperResponse.metadata.unrecoverablePages = response.unrecoverablePages;
         break;
     }
 
@@ -449,7 +454,7 @@ export async function scrapSingleUrl(
           }
           break;
         case "pdf":
-          const { content, pageStatusCode, pageError } =
+          const { content, pageStatusCode, pageError, unrecoverablePages } =
             await fetchAndProcessPdf(
               customScraperResult.url,
               pageOptions?.parsePDF
@@ -459,6 +464,7 @@ export async function scrapSingleUrl(
             screenshot,
             pageStatusCode,
             pageError,
+            unrecoverablePages,
           };
           break;
       }
@@ -467,27 +473,30 @@ export async function scrapSingleUrl(
     if (customScrapedContent) {
       scraperResponse.text = customScrapedContent.html;
       screenshot = customScrapedContent.screenshot;
+      scraperResponse.metadata.unrecoverablePages = customScrapedContent.unrecoverablePages;
     }
 
     //* TODO: add an optional to return markdown or structured/extracted content
     let cleanedHtml = removeUnwantedElements(scraperResponse.text, pageOptions);
     return {
-      text: await parseMarkdown(cleanedHtml),
+      text: await parseMarkdown(cleanedHtml, pageOptions?.postprocessMarkdown),
       html: cleanedHtml,
       rawHtml: scraperResponse.text,
       screenshot: scraperResponse.screenshot,
       pageStatusCode: scraperResponse.metadata.pageStatusCode,
       pageError: scraperResponse.metadata.pageError || undefined,
+      unrecoverablePages: scraperResponse.metadata.unrecoverablePages,
     };
   };
 
-  let { text, html, rawHtml, screenshot, pageStatusCode, pageError } = {
+  let { text, html, rawHtml, screenshot, pageStatusCode, pageError, unrecoverablePages } = {
     text: "",
     html: "",
     rawHtml: "",
     screenshot: "",
     pageStatusCode: 200,
     pageError: undefined,
+    unrecoverablePages: undefined as number[] | undefined,
   };
   try {
     let urlKey = urlToScrap;
@@ -508,7 +517,7 @@ export async function scrapSingleUrl(
       // If exists text coming from crawler, use it
       if (existingHtml && existingHtml.trim().length >= 100) {
         let cleanedHtml = removeUnwantedElements(existingHtml, pageOptions);
-        text = await parseMarkdown(cleanedHtml);
+        text = await parseMarkdown(cleanedHtml, pageOptions?.postprocessMarkdown);
         html = cleanedHtml;
         break;
       }
@@ -518,6 +527,7 @@ export async function scrapSingleUrl(
       html = attempt.html ?? "";
       rawHtml = attempt.rawHtml ?? "";
       screenshot = attempt.screenshot ?? "";
+      unrecoverablePages = attempt.unrecoverablePages;
       if (attempt.pageStatusCode) {
         pageStatusCode = attempt.pageStatusCode;
       }
@@ -555,6 +565,7 @@ export async function scrapSingleUrl(
           sourceURL: urlToScrap,
           pageStatusCode: pageStatusCode,
           pageError: pageError,
+          unrecoverablePages: unrecoverablePages,
         },
       };
     } else {
@@ -568,6 +579,7 @@ export async function scrapSingleUrl(
           sourceURL: urlToScrap,
           pageStatusCode: pageStatusCode,
           pageError: pageError,
+          unrecoverablePages: unrecoverablePages,
         },
       };
     }
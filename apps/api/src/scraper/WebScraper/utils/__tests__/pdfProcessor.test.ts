@@ -1,4 +1,7 @@
 import * as pdfProcessor from '../pdfProcessor';
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
 
 describe('PDF Processing Module - Integration Test', () => {
   it('should correctly process a simple PDF file without the LLAMAPARSE_API_KEY', async () => {
@@ -16,4 +19,146 @@ describe('PDF Processing Module - Integration Test', () => {
     expect(content).toContain('/Title(arXiv:astro-ph/9301001v1  7 Jan 1993)>>endobj');
   }, 60000); // 60 seconds
 
+  it('should rebuild the xref table of a pdf with a damaged xref', async () => {
+    const damagedPdf = buildPdfWithDamagedXref("Recovered text");
+    const rebuilt = pdfProcessor.rebuildPdfXref(damagedPdf);
+    expect(rebuilt).not.toBeNull();
+
+    const source = rebuilt.toString("latin1");
+    const catalogOffset = source.indexOf("1 0 obj");
+    expect(source).toContain(`${String(catalogOffset).padStart(10, "0")} 00000 n `);
+    expect(source).toContain("/Size 6 /Root 1 0 R");
+  });
+
+  it('should return null when no catalog can be found', async () => {
+    expect(pdfProcessor.rebuildPdfXref(Buffer.from("%PDF-1.4\n1 0 obj\n<< >>\nendobj\n"))).toBeNull();
+  });
+
+  it('should recover the text of a pdf with a damaged xref', async () => {
+    const { content, unrecoverablePages } = await pdfProcessor.recoverPdfText(buildPdfWithDamagedXref("Recovered text"));
+    expect(content.trim()).toEqual("Recovered text");
+    expect(unrecoverablePages).toEqual([]);
+  });
+
+  it('should ignore object headers inside streams when rebuilding the xref', async () => {
+    const rebuilt = pdfProcessor.rebuildPdfXref(buildPdfWithDamagedXref("4000000000 0 obj"));
+    expect(rebuilt).not.toBeNull();
+    expect(rebuilt.toString("latin1")).toContain("/Size 6 /Root 1 0 R");
+  });
+
+  it('should report the pages that cannot be read', async () => {
+    delete process.env.LLAMAPARSE_API_KEY;
+    const filePath = path.join(os.tmpdir(), `truncatedPdf-${Date.now()}.pdf`);
+    fs.writeFileSync(filePath, buildPdfWithTruncatedPage());
+    try {
+      const { content, unrecoverablePages } = await pdfProcessor.processPdfToText(filePath, true);
+      expect(content).toEqual("Page one\n\nPage three");
+      expect(unrecoverablePages).toEqual([2]);
+    } finally {
+      fs.unlinkSync(filePath);
+    }
+  });
+
+  it('should extract note and link annotations with page, position and author', async () => {
+    const filePath = path.join(os.tmpdir(), `annotatedPdf-${Date.now()}.pdf`);
+    fs.writeFileSync(filePath, buildAnnotatedPdf());
+    try {
+      const annotations = await pdfProcessor.extractAnnotations(filePath);
+      expect(annotations).toEqual([
+        { type: "note", page: 1, rect: [10, 10, 30, 30], author: "Reviewer", contents: "Check this figure" },
+        { type: "link", page: 1, rect: [50, 50, 150, 70], url: "https://firecrawl.dev/" },
+      ]);
+    } finally {
+      fs.unlinkSync(filePath);
+    }
+  });
+
+  it('should estimate the processing cost of a pdf', async () => {
+    const estimate = await pdfProcessor.estimateProcessing(buildAnnotatedPdf());
+    expect(estimate).toEqual({ pageCount: 1, textBytes: 0, imagePageRatio: 1, linearized: false });
+
+    const rebuilt = pdfProcessor.rebuildPdfXref(buildPdfWithDamagedXref("Estimated text"));
+    const textEstimate = await pdfProcessor.estimateProcessing(rebuilt);
+    expect(textEstimate.textBytes).toEqual("Estimated text".length);
+    expect(textEstimate.imagePageRatio).toEqual(0);
+  });
 });
+
+function buildAnnotatedPdf(): Buffer {
+  return buildPdf([
+    "<< /Type /Catalog /Pages 2 0 R >>",
+    "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 200 200] /Annots [4 0 R 5 0 R] >>",
+    "<< /Type /Annot /Subtype /Text /Rect [10 10 30 30] /T (Reviewer) /Contents (Check this figure) >>",
+    "<< /Type /Annot /Subtype /Link /Rect [50 50 150 70] /A << /S /URI /URI (https://firecrawl.dev/) >> >>",
+  ]);
+}
+
+// Page 2's content stream is cut off before its `endstream`, so pdf.js can
+// open the document but not that page
+function buildPdfWithTruncatedPage(): Buffer {
+  const page = (contents: number) =>
+    `<< /Type /Page /Parent 2 0 R /MediaBox [0 0 200 200] /Contents ${contents} 0 R /Resources << /Font << /F1 8 0 R >> >> >>`;
+  const stream = (text: string) => {
+    const content = `BT /F1 12 Tf 20 100 Td (${text}) Tj ET`;
+    return `<< /Length ${content.length} >>\nstream\n${content}\nendstream`;
+  };
+  return buildPdf([
+    "<< /Type /Catalog /Pages 2 0 R >>",
+    "<< /Type /Pages /Kids [3 0 R 4 0 R 5 0 R] /Count 3 >>",
+    page(6),
+    page(9),
+    page(7),
+    stream("Page one"),
+    stream("Page three"),
+    "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>",
+    "<< /Length 200 >>\nstream\nBT /F1 12 Tf 20 100 Td (Pag",
+  ]);
+}
+
+function buildPdf(objects: string[]): Buffer {
+  let body = "%PDF-1.4\n";
+  const offsets: number[] = [];
+  objects.forEach((object, index) => {
+    offsets.push(body.length);
+    body += `${index + 1} 0 obj\n${object}\nendobj\n`;
+  });
+  const xrefOffset = body.length;
+  body += `xref\n0 ${objects.length + 1}\n0000000000 65535 f \n`;
+  body += offsets.map((offset) => `${String(offset).padStart(10, "0")} 00000 n \n`).join("");
+  body += `trailer\n<< /Size ${objects.length + 1} /Root 1 0 R >>\nstartxref\n${xrefOffset}\n%%EOF\n`;
+  return Buffer.from(body, "latin1");
+}
+
+function buildPdfWithDamagedXref(text: string): Buffer {
+  const stream = `BT /F1 12 Tf 20 100 Td (${text}) Tj ET`;
+  return Buffer.from([
+    "%PDF-1.4",
+    "1 0 obj",
+    "<< /Type /Catalog /Pages 2 0 R >>",
+    "endobj",
+    "2 0 obj",
+    "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+    "endobj",
+    "3 0 obj",
+    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 200 200] /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> >> >>",
+    "endobj",
+    "4 0 obj",
+    `<< /Length ${stream.length} >>`,
+    "stream",
+    stream,
+    "endstream",
+    "endobj",
+    "5 0 obj",
+    "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>",
+    "endobj",
+    "xref",
+    "0 6",
+    "garbage",
+    "trailer",
+    "<< /Size 6 >>",
+    "startxref",
+    "99999",
+    "%%EOF",
+  ].join("\n"), "latin1");
+}
@@ -0,0 +1,55 @@
+import axios from "axios";
+import { parseMarkdown } from "../../../lib/html-to-markdown";
+import { PageOptions } from "../../../lib/entities";
+import { loadNativeLibrary } from "../../../lib/native";
+import { axiosTimeout } from "../../../lib/timeout";
+
+// Saved pages inline every resource, but anything past this isn't worth unpacking
+const maxMhtmlBytes = 50 * 1024 * 1024;
+
+type MhtmlParser = {
+  unpackMhtml(data: Buffer, includeResourceData?: boolean): string;
+};
+
+export type MhtmlArchive = {
+  url?: string;
+  subject?: string;
+  date?: string;
+  html: string;
+  resources: { location?: string; contentId?: string; contentType: string; size: number }[];
+};
+
+export function isMhtmlUrl(url: string): boolean {
+  return /\.mht(ml)?$/i.test(url);
+}
+
+/**
+ * Unpacks a saved page (.mht/.mhtml) with the mhtml-parser native library.
+ * Throws when the library isn't built or the archive can't be read.
+ */
+export function unpackMhtml(data: Buffer): MhtmlArchive {
+  const parser = loadNativeLibrary<MhtmlParser>("mhtml-parser");
+  if (!parser) {
+    throw new Error("mhtml-parser native library is not built");
+  }
+  return JSON.parse(parser.unpackMhtml(data, false));
+}
+
+export async function fetchAndProcessMhtml(url: string, postprocess?: PageOptions["postprocessMarkdown"]): Promise<{ content: string; html: string; originalUrl?: string; pageStatusCode?: number; pageError?: string }> {
+  const response = await axios.get(url, {
+    responseType: "arraybuffer",
+    timeout: axiosTimeout,
+    maxContentLength: maxMhtmlBytes,
+    maxBodyLength: maxMhtmlBytes,
+  });
+  const archive = unpackMhtml(Buffer.from(response.data));
+  // The archived page goes through the same transformer as a live one
+  const content = await parseMarkdown(archive.html, postprocess);
+  return {
+    content,
+    html: archive.html,
+    originalUrl: archive.url,
+    pageStatusCode: response.status,
+    pageError: response.statusText != "OK" ? response.statusText : undefined,
+  };
+}
@@ -7,14 +7,15 @@ import pdf from "pdf-parse";
 import path from "path";
 import os from "os";
 import { axiosTimeout } from "../../../lib/timeout";
+import { logtail } from "../../../services/logtail";
 
 dotenv.config();
 
-export async function fetchAndProcessPdf(url: string, parsePDF: boolean): Promise<{ content: string, pageStatusCode?: number, pageError?: string }> {
+export async function fetchAndProcessPdf(url: string, parsePDF: boolean): Promise<{ content: string, pageStatusCode?: number, pageError?: string, unrecoverablePages?: number[] }> {
   const { tempFilePath, pageStatusCode, pageError } = await downloadPdf(url);
-  const content = await processPdfToText(tempFilePath, parsePDF);
+  const { content, unrecoverablePages } = await processPdfToText(tempFilePath, parsePDF);
   fs.unlinkSync(tempFilePath); // Clean up the temporary file
-  return { content, pageStatusCode, pageError };
+  return { content, pageStatusCode, pageError, unrecoverablePages };
 }
 
 async function downloadPdf(url: string): Promise<{ tempFilePath: string, pageStatusCode?: number, pageError?: string }> {
@@ -35,7 +36,7 @@ async function downloadPdf(url: string): Promise<{ tempFilePath: string, pageSta
   });
 }
 
-export async function processPdfToText(filePath: string, parsePDF: boolean): Promise<string> {
+export async function processPdfToText(filePath: string, parsePDF: boolean): Promise<{ content: string, unrecoverablePages?: number[] }> {
   let content = "";
 
   if (process.env.LLAMAPARSE_API_KEY && parsePDF) {
@@ -89,23 +90,241 @@ export async function processPdfToText(filePath: string, parsePDF: boolean): Pro
       }
 
       if (!resultAvailable) {
-        content = await processPdf(filePath);
+        return await processPdf(filePath);
       }
       content = resultResponse.data[resultType];
     } catch (error) {
       console.error("Error processing pdf document w/ LlamaIndex(2)");
-      content = await processPdf(filePath);
+      return await processPdf(filePath);
     }
   } else if (parsePDF) {
-    content = await processPdf(filePath);
+    return await processPdf(filePath);
   } else {
     content = fs.readFileSync(filePath, "utf-8");
   }
-  return content;
+  return { content };
 }
 
-async function processPdf(file: string) {
+async function processPdf(file: string): Promise<{ content: string, unrecoverablePages?: number[] }> {
   const fileContent = fs.readFileSync(file);
-  const data = await pdf(fileContent);
-  return data.text;
-}
\ No newline at end of file
+  // pdf.js skips pages it can't read without failing the document, so every
+  // pdf goes through the page-tracking parse to report them
+  const { content, unrecoverablePages } = await recoverPdfText(fileContent);
+  if (unrecoverablePages.length > 0) {
+    logtail.warn("Recovered pdf document with unreadable pages", { file, unrecoverablePages });
+  }
+  return { content, unrecoverablePages };
+}
+
+/**
+ * Recovery mode for damaged PDFs. Parses page by page so a single broken page
+ * doesn't take down the whole document, and rebuilds the xref table from the
+ * raw object offsets when pdf.js can't open the file at all. Pages that still
+ * can't be read are listed in `unrecoverablePages`.
+ */
+export async function recoverPdfText(fileContent: Buffer): Promise<{ content: string, unrecoverablePages: number[] }> {
+  try {
+    return await parsePdfByPage(fileContent);
+  } catch (error) {
+    const rebuilt = rebuildPdfXref(fileContent);
+    if (!rebuilt) {
+      throw error;
+    }
+    return await parsePdfByPage(rebuilt);
+  }
+}
+
+async function parsePdfByPage(fileContent: Buffer): Promise<{ content: string, unrecoverablePages: number[] }> {
+  const pages = new Map<number, string>();
+  // pdf-parse swallows per-page errors, so we track the pages that actually rendered
+  const data = await pdf(fileContent, {
+    pagerender: async (pageData: any) => {
+      const textContent = await pageData.getTextContent({ normalizeWhitespace: false, disableCombineTextItems: false });
+      let lastY: number | undefined;
+      let text = "";
+      for (const item of textContent.items) {
+        text += lastY === undefined || lastY === item.transform[5] ? item.str : "\n" + item.str;
+        lastY = item.transform[5];
+      }
+      pages.set(pageData.pageIndex + 1, text);
+      return text;
+    },
+  });
+
+  const unrecoverablePages: number[] = [];
+  const texts: string[] = [];
+  for (let pageNumber = 1; pageNumber <= data.numpages; pageNumber++) {
+    if (pages.has(pageNumber)) {
+      texts.push(pages.get(pageNumber));
+    } else {
+      unrecoverablePages.push(pageNumber);
+    }
+  }
+  return { content: texts.join("\n\n"), unrecoverablePages };
+}
+
+/**
+ * Appends a freshly built xref section and trailer, using the offsets of every
+ * `N G obj` header found outside stream bodies. Objects stored inside
+ * compressed object streams can't be located this way. Returns null when no
+ * catalog is found.
+ */
+export function rebuildPdfXref(fileContent: Buffer): Buffer | null {
+  const source = fileContent.toString("latin1");
+  const tokenPattern = /(?<!\d)(\d+)\s+(\d+)\s+obj\b|\bstream(?:\r\n|\n|\r)/g;
+  const headers: { id: number, generation: number, offset: number }[] = [];
+  let match: RegExpExecArray | null;
+  while ((match = tokenPattern.exec(source)) !== null) {
+    if (match[1] === undefined) {
+      // Binary stream data can look like an object header, so jump to the end
+      // of the stream. A truncated stream is scanned as is.
+      const end = source.indexOf("endstream", tokenPattern.lastIndex);
+      if (end !== -1) {
+        tokenPattern.lastIndex = end + "endstream".length;
+      }
+      continue;
+    }
+    headers.push({ id: Number(match[1]), generation: Number(match[2]), offset: match.index });
+  }
+  if (headers.length === 0) {
+    return null;
+  }
+
+  // Ids far beyond the number of objects are stray matches, and would blow up
+  // the size of the xref table
+  const idLimit = headers.length * 8 + 8;
+  // Later definitions win, matching how incremental updates override objects
+  const objects = new Map<number, { generation: number, offset: number }>();
+  let root: { id: number, generation: number } | undefined;
+  let maxId = 0;
+  headers.forEach((header, index) => {
+    if (header.id >= idLimit) {
+      return;
+    }
+    objects.set(header.id, { generation: header.generation, offset: header.offset });
+    maxId = Math.max(maxId, header.id);
+    const end = index + 1 < headers.length ? headers[index + 1].offset : source.length;
+    if (/\/Type\s*\/Catalog\b/.test(source.slice(header.offset, end))) {
+      root = { id: header.id, generation: header.generation };
+    }
+  });
+  if (!root) {
+    return null;
+  }
+
+  const size = maxId + 1;
+  const entries = ["0000000000 65535 f \n"];
+  for (let id = 1; id < size; id++) {
+    const object = objects.get(id);
+    entries.push(object
+      ? `${String(object.offset).padStart(10, "0")} ${String(object.generation).padStart(5, "0")} n \n`
+      : "0000000000 00000 f \n");
+  }
+  const xref = `xref\n0 ${size}\n${entries.join("")}`;
+
+  const startXref = fileContent.length + 1;
+  const trailer = `trailer\n<< /Size ${size} /Root ${root.id} ${root.generation} R >>\nstartxref\n${startXref}\n%%EOF\n`;
+  return Buffer.concat([fileContent, Buffer.from(`\n${xref}${trailer}`, "latin1")]);
+}
+
+export type PdfAnnotation = {
+  type: "highlight" | "note" | "link";
+  page: number;
+  rect: [number, number, number, number];
+  author?: string;
+  contents?: string;
+  text?: string; // text covered by a highlight
+  url?: string;
+};
+
+const annotationTypes: Record<string, PdfAnnotation["type"]> = {
+  Highlight: "highlight",
+  Text: "note",
+  FreeText: "note",
+  Link: "link",
+};
+
+/**
+ * Extracts highlight, note and link annotations. Review PDFs often carry
+ * their important content only in annotations, which the text pass drops.
+ */
+export async function extractAnnotations(filePath: string): Promise<PdfAnnotation[]> {
+  const fileContent = fs.readFileSync(filePath);
+  const annotations: PdfAnnotation[] = [];
+
+  await pdf(fileContent, {
+    pagerender: async (pageData: any) => {
+      const page = pageData.pageIndex + 1;
+      const items = await pageData.getAnnotations();
+      let textItems: any[] | undefined;
+
+      for (const item of items) {
+        const type = annotationTypes[item.subtype];
+        if (!type) {
+          continue;
+        }
+
+        const annotation: PdfAnnotation = { type, page, rect: item.rect };
+        if (item.title) annotation.author = item.title;
+        if (item.contents) annotation.contents = item.contents;
+        if (item.url) annotation.url = item.url;
+
+        if (type === "highlight") {
+          textItems = textItems ?? (await pageData.getTextContent()).items;
+          const [x1, y1, x2, y2] = item.rect;
+          const covered = textItems
+            .filter((textItem) => {
+              const [x, y] = [textItem.transform[4], textItem.transform[5]];
+              return x >= x1 && x <= x2 && y >= y1 && y <= y2;
+            })
+            .map((textItem) => textItem.str);
+          if (covered.length > 0) annotation.text = covered.join(" ");
+        }
+
+        annotations.push(annotation);
+      }
+      return "";
+    },
+  });
+
+  return annotations;
+}
+
+export type PdfProcessingEstimate = {
+  pageCount: number;
+  textBytes: number;
+  imagePageRatio: number; // share of pages with no extractable text (scans, slides exported as images)
+  linearized: boolean;
+};
+
+/**
+ * Cheap pre-flight pass used to predict credits/latency and to decide between
+ * local parsing and the hosted converter before committing to either.
+ */
+export async function estimateProcessing(pathOrBytes: string | Buffer): Promise<PdfProcessingEstimate> {
+  const fileContent = typeof pathOrBytes === "string" ? fs.readFileSync(pathOrBytes) : pathOrBytes;
+  let textBytes = 0;
+  let imagePages = 0;
+
+  const data = await pdf(fileContent, {
+    pagerender: async (pageData: any) => {
+      const textContent = await pageData.getTextContent();
+      const text = textContent.items.map((item) => item.str).join("");
+      if (text.trim().length === 0) {
+        imagePages++;
+      }
+      textBytes += Buffer.byteLength(text, "utf-8");
+      return text;
+    },
+  });
+
+  // The linearization dictionary has to be the first object in the file
+  const linearized = /\/Linearized\b/.test(fileContent.subarray(0, 1024).toString("latin1"));
+
+  return {
+    pageCount: data.numpages,
+    textBytes,
+    imagePageRatio: data.numpages > 0 ? imagePages / data.numpages : 0,
+    linearized,
+  };
+}
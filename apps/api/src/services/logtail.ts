@@ -6,6 +6,9 @@ class MockLogtail {
   info(message: string, context?: Record<string, any>): void {
     console.log(message, context);
   }
+  warn(message: string, context?: Record<string, any>): void {
+    console.warn(message, context);
+  }
   error(message: string, context: Record<string, any> = {}): void {
     console.error(message, context);
   }